@@ -0,0 +1,142 @@
+//! Frames of a shot played without a window, exported to PNG files or to
+//! an animated GIF.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use gif::{Encoder, Frame, Repeat, SetParameter};
+
+use super::diagram::Diagram;
+use super::shot::Shot;
+use super::PoolTable;
+
+/// A frame every FRAME_STEPS steps, 30 frames per second.
+pub const FRAME_STEPS: usize = 2;
+// delays of the GIF frames, in hundredths of seconds
+const FRAME_DELAY: u16 = 3;
+const LAST_FRAME_DELAY: u16 = 150;
+// quality of the GIF palettes, from 1 (best, slowest) to 30
+const GIF_QUANTIZATION: i32 = 10;
+
+/// The frames of a shot, from the table before the shot to the balls at rest.
+pub struct Animation {
+    frames: Vec<Diagram>,
+}
+
+impl Animation {
+    /// Start with the table before the shot, the shot drawn as an arrow.
+    pub fn new(pool_table: &PoolTable, shot: &Shot) -> Self {
+        Animation {
+            frames: vec![Diagram::of(pool_table).with_shot(*shot)],
+        }
+    }
+
+    /// Add the table after a step of `simulation::play`, every FRAME_STEPS
+    /// steps and when the balls are at rest.
+    pub fn record(&mut self, step: usize, pool_table: &PoolTable) {
+        if step % FRAME_STEPS == FRAME_STEPS - 1 || !pool_table.has_force() {
+            self.frames.push(Diagram::of(pool_table));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Write the frames as `frame-0000.png`, `frame-0001.png`... in a directory.
+    pub fn write_frames<P: AsRef<Path>>(&self, dir: P, width: u32) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        for (index, frame) in self.frames.iter().enumerate() {
+            let png = frame.to_png(width)?;
+            fs::write(dir.join(format!("frame-{:04}.png", index)), png)?;
+        }
+        Ok(())
+    }
+
+    /// The frames as an animated GIF that loops, the last frame held a moment.
+    pub fn to_gif(&self, width: u32) -> io::Result<Vec<u8>> {
+        let height = Diagram::height(width);
+        let mut gif = Vec::new();
+        {
+            let mut encoder = Encoder::new(&mut gif, width as u16, height as u16, &[])?;
+            encoder.set(Repeat::Infinite)?;
+            for (index, diagram) in self.frames.iter().enumerate() {
+                let (_, _, mut data) = diagram.to_rgba(width);
+                let mut frame =
+                    Frame::from_rgba_speed(width as u16, height as u16, &mut data, GIF_QUANTIZATION);
+                frame.delay = if index + 1 == self.frames.len() {
+                    LAST_FRAME_DELAY
+                } else {
+                    FRAME_DELAY
+                };
+                encoder.write_frame(&frame)?;
+            }
+        }
+        Ok(gif)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation;
+    use crate::GameMode;
+    use ncollide2d::math::Vector as Vector2;
+
+    const WIDTH: u32 = 64;
+
+    fn animation() -> Animation {
+        let mut pool_table = PoolTable::new();
+        pool_table.initialze_world(GameMode::Practice);
+        let shot = Shot {
+            angle: 0.,
+            force: 200.,
+            spin: Vector2::new(0., 0.),
+            elevation: 0.,
+        };
+        let mut animation = Animation::new(&pool_table, &shot);
+        let steps = simulation::play(&mut pool_table, &shot, |step, pool_table, _| {
+            animation.record(step, pool_table)
+        });
+        // the shot, every other step and the last one
+        assert_eq!(animation.len(), 1 + (steps + FRAME_STEPS - 1) / FRAME_STEPS);
+        animation
+    }
+
+    #[test]
+    fn the_gif_loops_over_every_frame() {
+        let animation = animation();
+        let gif = animation.to_gif(WIDTH).unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+        let size = |at: usize| u16::from(gif[at]) | u16::from(gif[at + 1]) << 8;
+        assert_eq!(size(6), WIDTH as u16);
+        assert_eq!(size(8), Diagram::height(WIDTH) as u16);
+        assert_eq!(gif.last(), Some(&b';'));
+        // a graphic control block before every frame, the compressed
+        // pixels may contain the same bytes
+        let frames = gif.windows(3).filter(|w| *w == b"\x21\xf9\x04").count();
+        assert!(frames >= animation.len());
+    }
+
+    #[test]
+    fn every_frame_is_written() {
+        let animation = animation();
+        let name = format!("wasm-pool-frames-{}", std::process::id());
+        let dir = std::env::temp_dir().join(name);
+        animation.write_frames(&dir, WIDTH).unwrap();
+        let mut files: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files.len(), animation.len());
+        assert_eq!(files[0], "frame-0000.png");
+    }
+}
@@ -0,0 +1,68 @@
+//! Images of the balls, rendered once per kind and orientation.
+
+use std::collections::HashMap;
+
+use nalgebra::{Quaternion, UnitQuaternion};
+
+use quicksilver::{
+    graphics::{Image, PixelFormat},
+    Result,
+};
+
+use super::sprite::{self, BallStyle};
+use super::BallKind;
+
+// steps of the components of the orientations, about 7 degrees
+const ORIENTATION_STEPS: f32 = 16.;
+// the images of the rolling balls are dropped past this count
+const MAX_SPRITES: usize = 512;
+
+type Key = (BallKind, [i8; 4]);
+
+/// The ball sprites uploaded to the graphic backend, a rolling ball only
+/// gets a new image when its orientation changes by a step.
+pub struct BallSprites {
+    size: u32,
+    images: HashMap<Key, Image>,
+}
+
+impl BallSprites {
+    pub fn new(size: u32) -> Self {
+        BallSprites {
+            size,
+            images: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, kind: BallKind, orientation: &UnitQuaternion<f32>) -> Result<&Image> {
+        let key = (kind, quantize(orientation));
+        if !self.images.contains_key(&key) {
+            if self.images.len() >= MAX_SPRITES {
+                self.images.clear();
+            }
+            let pixels = sprite::ball(self.size, &BallStyle::of(kind), &dequantize(key.1));
+            let image = Image::from_raw(&pixels, self.size, self.size, PixelFormat::RGBA)?;
+            self.images.insert(key, image);
+        }
+        Ok(&self.images[&key])
+    }
+}
+
+fn quantize(orientation: &UnitQuaternion<f32>) -> [i8; 4] {
+    let q = orientation.quaternion();
+    // q and -q are the same rotation
+    let sign = if q.w < 0. { -1. } else { 1. };
+    let step = |value: f32| (value * sign * ORIENTATION_STEPS).round() as i8;
+    [step(q.w), step(q.i), step(q.j), step(q.k)]
+}
+
+// the orientation drawn for a key, the same for every orientation of a step
+fn dequantize(steps: [i8; 4]) -> UnitQuaternion<f32> {
+    let [w, i, j, k] = steps;
+    let value = |step: i8| step as f32 / ORIENTATION_STEPS;
+    let q = Quaternion::new(value(w), value(i), value(j), value(k));
+    if q.norm() == 0. {
+        return UnitQuaternion::identity();
+    }
+    UnitQuaternion::from_quaternion(q)
+}
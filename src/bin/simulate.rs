@@ -0,0 +1,329 @@
+//! Play a shot without a window and print the result as JSON.
+//!
+//! ```text
+//! simulate [--mode 8|9|p] [--profile NAME] [--table FILE.json] [--position NOTATION]
+//!          --angle DEGREES --force FORCE [--side SPIN] [--follow SPIN] [--elevation DEGREES]
+//!          [--svg FILE.svg] [--png FILE.png] [--gif FILE.gif] [--frames DIR]
+//! ```
+//!
+//! The table is the rack of the mode, a `TableState` read from a JSON
+//! file, or a position in the compact notation. `--profile` picks the
+//! physics of the table, the one of the layout or "standard" by default.
+//! `--force` is clamped to MAX_FORCE, as in the game. `--elevation` raises
+//! the cue for a jump or a massé. `--svg` and `--png` also
+//! write a diagram of the table before the shot, with the predicted paths,
+//! `--gif` an animation of the shot and `--frames` its frames as PNG files.
+
+use std::env;
+use std::fs;
+use std::process;
+
+use ncollide2d::math::Vector as Vector2;
+use nphysics2d::object::BodyHandle;
+use serde_derive::Serialize;
+
+use wasm_pool::animation::Animation;
+use wasm_pool::diagram::Diagram;
+use wasm_pool::notation::Position;
+use wasm_pool::physics::PhysicsProfile;
+use wasm_pool::shot::{clamp_elevation, clamp_force, clamp_spin, Shot};
+use wasm_pool::simulation;
+use wasm_pool::state::{TableState, REST_TOLERANCE};
+use wasm_pool::{BallKind, GameMode, PoolTable, TableEvent};
+
+const USAGE: &str = "usage: simulate [--mode 8|9|p] [--profile NAME] [--table FILE.json] \
+                     [--position NOTATION] \
+                     --angle DEGREES --force FORCE [--side SPIN] [--follow SPIN] \
+                     [--elevation DEGREES] \
+                     [--svg FILE.svg] [--png FILE.png] [--gif FILE.gif] [--frames DIR]";
+
+const DIAGRAM_WIDTH: u32 = 1024;
+// smaller, an animation has hundreds of frames
+const ANIMATION_WIDTH: u32 = 640;
+
+#[derive(Serialize)]
+struct Pocketed {
+    ball: BallKind,
+    // index of the hole, none when the ball left the table
+    hole: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Event {
+    CueStrike {
+        step: usize,
+        speed: f32,
+    },
+    BallHit {
+        step: usize,
+        balls: [Option<BallKind>; 2],
+        speed: f32,
+    },
+    CushionHit {
+        step: usize,
+        ball: Option<BallKind>,
+        speed: f32,
+    },
+    Pocketed {
+        step: usize,
+        ball: Option<BallKind>,
+        hole: Option<usize>,
+        speed: f32,
+    },
+    OffTable {
+        step: usize,
+        ball: Option<BallKind>,
+        speed: f32,
+    },
+}
+
+#[derive(Serialize)]
+struct Report {
+    steps: usize,
+    // false when the balls still moved after simulation::MAX_STEPS
+    at_rest: bool,
+    // first ball touched by the cue ball
+    first_hit: Option<BallKind>,
+    // the tip slid off the cue ball, see MiscueLimits
+    miscue: bool,
+    table: TableState,
+    pocketed: Vec<Pocketed>,
+    events: Vec<Event>,
+}
+
+struct Args {
+    mode: GameMode,
+    profile: Option<PhysicsProfile>,
+    table: Option<String>,
+    position: Option<String>,
+    angle: Option<f32>,
+    force: Option<f32>,
+    side: f32,
+    follow: f32,
+    elevation: f32,
+    svg: Option<String>,
+    png: Option<String>,
+    gif: Option<String>,
+    frames: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        mode: GameMode::EightBall,
+        profile: None,
+        table: None,
+        position: None,
+        angle: None,
+        force: None,
+        side: 0.,
+        follow: 0.,
+        elevation: 0.,
+        svg: None,
+        png: None,
+        gif: None,
+        frames: None,
+    };
+    let mut argv = env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--mode" => {
+                let mode = value(&mut argv, &arg)?;
+                args.mode = match mode.as_str() {
+                    "8" => GameMode::EightBall,
+                    "9" => GameMode::NineBall,
+                    "p" => GameMode::Practice,
+                    _ => return Err(format!("unknown mode {}", mode)),
+                }
+            }
+            "--profile" => {
+                let name = value(&mut argv, &arg)?;
+                args.profile = Some(
+                    PhysicsProfile::by_name(&name)
+                        .ok_or_else(|| format!("unknown profile {}", name))?,
+                )
+            }
+            "--table" => args.table = Some(value(&mut argv, &arg)?),
+            "--position" => args.position = Some(value(&mut argv, &arg)?),
+            "--angle" => args.angle = Some(number(&mut argv, &arg)?),
+            "--force" => args.force = Some(number(&mut argv, &arg)?),
+            "--side" => args.side = number(&mut argv, &arg)?,
+            "--follow" => args.follow = number(&mut argv, &arg)?,
+            "--elevation" => args.elevation = number(&mut argv, &arg)?,
+            "--svg" => args.svg = Some(value(&mut argv, &arg)?),
+            "--png" => args.png = Some(value(&mut argv, &arg)?),
+            "--gif" => args.gif = Some(value(&mut argv, &arg)?),
+            "--frames" => args.frames = Some(value(&mut argv, &arg)?),
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+    Ok(args)
+}
+
+// the value of a flag, another flag is not one
+fn value<I: Iterator<Item = String>>(argv: &mut I, flag: &str) -> Result<String, String> {
+    match argv.next() {
+        Some(ref value) if value.starts_with("--") => Err(format!("missing value for {}", flag)),
+        Some(value) => Ok(value),
+        None => Err(format!("missing value for {}", flag)),
+    }
+}
+
+fn number<I: Iterator<Item = String>>(argv: &mut I, flag: &str) -> Result<f32, String> {
+    let value = value(argv, flag)?;
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or_else(|| format!("{} is not a number: {}", flag, value))
+}
+
+fn load_table(args: &Args) -> Result<PoolTable, String> {
+    let state: TableState = if let Some(ref path) = args.table {
+        let json = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        serde_json::from_str(&json).map_err(|err| format!("{}: {}", path, err))?
+    } else if let Some(ref notation) = args.position {
+        let position: Position = notation.parse().map_err(|err| format!("{}", err))?;
+        position.table
+    } else {
+        let profile = args.profile.unwrap_or_else(PhysicsProfile::standard);
+        let mut pool_table = PoolTable::with_profile(profile);
+        pool_table.initialze_world(args.mode);
+        return Ok(pool_table);
+    };
+    state
+        .build_checked_within(args.profile.unwrap_or(state.profile), REST_TOLERANCE)
+        .map_err(|err| format!("{}", err))
+}
+
+// the kind of a ball, on the table or pocketed
+fn kind(pool_table: &PoolTable, ball: BodyHandle) -> Option<BallKind> {
+    pool_table
+        .ball_kind(ball)
+        .or_else(|| pool_table.pocketed_ball(ball).map(|pocketed| pocketed.kind))
+}
+
+fn record(step: usize, pool_table: &PoolTable, event: &TableEvent) -> Event {
+    match *event {
+        TableEvent::CueStrike { speed } => Event::CueStrike { step, speed },
+        TableEvent::BallHit { ball1, ball2, speed } => Event::BallHit {
+            step,
+            balls: [kind(pool_table, ball1), kind(pool_table, ball2)],
+            speed,
+        },
+        TableEvent::CushionHit { ball, speed } => Event::CushionHit {
+            step,
+            ball: kind(pool_table, ball),
+            speed,
+        },
+        TableEvent::Pocketed { ball, hole, speed } => Event::Pocketed {
+            step,
+            ball: kind(pool_table, ball),
+            hole: pool_table.hole_index(hole),
+            speed,
+        },
+        TableEvent::OffTable { ball, speed } => Event::OffTable {
+            step,
+            ball: kind(pool_table, ball),
+            speed,
+        },
+    }
+}
+
+fn write_diagram(args: &Args, pool_table: &PoolTable, shot: &Shot) -> Result<(), String> {
+    let diagram = Diagram::of(pool_table)
+        .with_shot(*shot)
+        .with_predicted_paths(shot);
+    if let Some(ref path) = args.svg {
+        fs::write(path, diagram.to_svg(DIAGRAM_WIDTH))
+            .map_err(|err| format!("{}: {}", path, err))?;
+    }
+    if let Some(ref path) = args.png {
+        let png = diagram
+            .to_png(DIAGRAM_WIDTH)
+            .map_err(|err| format!("{}: {}", path, err))?;
+        fs::write(path, png).map_err(|err| format!("{}: {}", path, err))?;
+    }
+    Ok(())
+}
+
+fn write_animation(args: &Args, animation: &Animation) -> Result<(), String> {
+    if let Some(ref path) = args.gif {
+        let gif = animation
+            .to_gif(ANIMATION_WIDTH)
+            .map_err(|err| format!("{}: {}", path, err))?;
+        fs::write(path, gif).map_err(|err| format!("{}: {}", path, err))?;
+    }
+    if let Some(ref dir) = args.frames {
+        animation
+            .write_frames(dir, ANIMATION_WIDTH)
+            .map_err(|err| format!("{}: {}", dir, err))?;
+    }
+    Ok(())
+}
+
+fn run() -> Result<Report, String> {
+    let args = parse_args()?;
+    let shot = Shot {
+        angle: args.angle.ok_or("missing --angle")?,
+        force: clamp_force(args.force.ok_or("missing --force")?),
+        spin: clamp_spin(Vector2::new(args.side, args.follow)),
+        elevation: clamp_elevation(args.elevation),
+    };
+    let mut pool_table = load_table(&args)?;
+    if pool_table.white_ball().is_none() {
+        return Err("there is no cue ball on the table".to_string());
+    }
+    if args.svg.is_some() || args.png.is_some() {
+        write_diagram(&args, &pool_table, &shot)?;
+    }
+
+    let mut animation = if args.gif.is_some() || args.frames.is_some() {
+        Some(Animation::new(&pool_table, &shot))
+    } else {
+        None
+    };
+    let mut events = Vec::new();
+    let steps = simulation::play(&mut pool_table, &shot, |step, pool_table, step_events| {
+        events.extend(step_events.iter().map(|event| record(step, pool_table, event)));
+        if let Some(ref mut animation) = animation {
+            animation.record(step, pool_table);
+        }
+    });
+    if let Some(ref animation) = animation {
+        write_animation(&args, animation)?;
+    }
+
+    let pocketed = pool_table
+        .pocketed_balls()
+        .iter()
+        .map(|pocketed| Pocketed {
+            ball: pocketed.kind,
+            hole: pocketed.hole,
+        })
+        .collect();
+    let summary = pool_table.take_shot_complete();
+    Ok(Report {
+        steps,
+        at_rest: summary.is_some(),
+        first_hit: summary.as_ref().and_then(|summary| summary.first_hit),
+        miscue: summary.map_or(false, |summary| summary.miscue),
+        table: TableState::of(&pool_table),
+        pocketed,
+        events,
+    })
+}
+
+fn main() {
+    match run() {
+        Ok(report) => println!("{}", serde_json::to_string_pretty(&report).unwrap()),
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            process::exit(2);
+        }
+    }
+}
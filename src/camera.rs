@@ -0,0 +1,242 @@
+#[cfg(target_arch = "wasm32")]
+use std::{cell::Cell, rc::Rc};
+
+use ncollide2d::math::Vector as Vector2;
+
+use quicksilver::geom::{Rectangle, Vector};
+
+use super::{BAND, BORDER, HEIGHT, MARGIN_LEFT, MARGIN_TOP, WIDTH};
+
+// screen pixels kept free around the table
+const PADDING: f32 = 10.;
+
+const MAX_ZOOM: f32 = 8.;
+// part of the distance to the followed point covered on every frame
+const FOLLOW_SMOOTHING: f32 = 0.08;
+
+/// World to screen transform.
+///
+/// The table, with its border, is centered and scaled to fit the screen.
+/// On a portrait screen, the table is rotated by 90 degrees.
+/// The view can then be zoomed, panned, and can follow a moving point.
+pub struct Camera {
+    screen: Vector,
+    // scale of the fitted table, without zoom
+    fit_scale: f32,
+    scale: f32,
+    zoom: f32,
+    rotated: bool,
+
+    // world position displayed at the center of the screen
+    focus: Vector2<f32>,
+    target: Option<Vector2<f32>>,
+}
+
+impl Camera {
+    pub fn new(screen: Vector) -> Self {
+        let mut camera = Camera {
+            screen: Vector::ZERO,
+            fit_scale: 1.,
+            scale: 1.,
+            zoom: 1.,
+            rotated: false,
+            focus: table_center(),
+            target: None,
+        };
+        camera.resize(screen);
+        camera
+    }
+
+    /// Recompute the transform if the screen size has changed.
+    pub fn resize(&mut self, screen: Vector) {
+        if screen == self.screen {
+            return;
+        }
+        info!("Resize the view to {:?}", screen);
+        self.screen = screen;
+        self.rotated = screen.y > screen.x;
+
+        let table = table_size();
+        let (table_width, table_height) = if self.rotated {
+            (table.y, table.x)
+        } else {
+            (table.x, table.y)
+        };
+        let scale_x = (screen.x - 2. * PADDING) / table_width;
+        let scale_y = (screen.y - 2. * PADDING) / table_height;
+        self.fit_scale = scale_x.min(scale_y).max(std::f32::EPSILON);
+        self.scale = self.fit_scale * self.zoom;
+        self.clamp_focus();
+    }
+
+    /// Zoom by the given factor, keeping the world point under `pos` in place.
+    pub fn zoom_at(&mut self, factor: f32, pos: Vector) {
+        let anchor = self.to_world(pos);
+        self.zoom = (self.zoom * factor).max(1.).min(MAX_ZOOM);
+        self.scale = self.fit_scale * self.zoom;
+
+        let offset = self.unrotate((pos - self.screen * 0.5) * (1. / self.scale));
+        self.focus = anchor - offset;
+        self.clamp_focus();
+    }
+
+    /// Move the view by a screen offset, this stops following.
+    pub fn pan(&mut self, delta: Vector) {
+        self.target = None;
+        self.focus -= self.unrotate(delta * (1. / self.scale));
+        self.clamp_focus();
+    }
+
+    /// Smoothly move the view toward the given world position.
+    pub fn follow(&mut self, pos: Vector2<f32>) {
+        self.target = Some(pos);
+    }
+
+    pub fn update(&mut self) {
+        if let Some(target) = self.target {
+            self.focus += (target - self.focus) * FOLLOW_SMOOTHING;
+            self.clamp_focus();
+        }
+    }
+
+    // keep the visible area inside the table, the view is centered on the
+    // table when it is fully visible.
+    fn clamp_focus(&mut self) {
+        let center = table_center();
+        let half_table = table_size() * 0.5;
+        let half_screen = self.unrotate(self.screen * (0.5 / self.scale));
+        let free_x = (half_table.x - half_screen.x.abs()).max(0.);
+        let free_y = (half_table.y - half_screen.y.abs()).max(0.);
+        self.focus.x = self.focus.x.max(center.x - free_x).min(center.x + free_x);
+        self.focus.y = self.focus.y.max(center.y - free_y).min(center.y + free_y);
+    }
+
+    // screen direction to world direction
+    fn unrotate(&self, vec: Vector) -> Vector2<f32> {
+        if self.rotated {
+            Vector2::new(vec.y, -vec.x)
+        } else {
+            Vector2::new(vec.x, vec.y)
+        }
+    }
+
+    pub fn screen_size(&self) -> Vector {
+        self.screen
+    }
+
+    /// Convert a world length to a screen length.
+    pub fn scale(&self, length: f32) -> f32 {
+        length * self.scale
+    }
+
+    /// Angle, in degrees, to add to a world rotation to get the screen one.
+    pub fn rotation(&self) -> f32 {
+        if self.rotated {
+            90.
+        } else {
+            0.
+        }
+    }
+
+    pub fn to_screen(&self, pos: Vector2<f32>) -> Vector {
+        let local = Vector::new(pos.x - self.focus.x, pos.y - self.focus.y);
+        let local = if self.rotated {
+            Vector::new(-local.y, local.x)
+        } else {
+            local
+        };
+        self.screen * 0.5 + local * self.scale
+    }
+
+    pub fn to_world(&self, pos: Vector) -> Vector2<f32> {
+        self.focus + self.unrotate((pos - self.screen * 0.5) * (1. / self.scale))
+    }
+
+    /// Screen rectangle of a world axis aligned rectangle, before the
+    /// camera rotation; it must be drawn rotated by `rotation()`.
+    pub fn world_rect(&self, top_left: Vector2<f32>, size: Vector2<f32>) -> Rectangle {
+        let center = self.to_screen(top_left + size * 0.5);
+        Rectangle::new_sized((self.scale(size.x), self.scale(size.y))).with_center(center)
+    }
+
+    /// Screen rectangle of a world axis aligned rectangle.
+    pub fn rect(&self, top_left: Vector2<f32>, size: Vector2<f32>) -> Rectangle {
+        let a = self.to_screen(top_left);
+        let b = self.to_screen(top_left + size);
+        Rectangle::new((a.x.min(b.x), a.y.min(b.y)), ((a.x - b.x).abs(), (a.y - b.y).abs()))
+    }
+}
+
+fn table_size() -> Vector2<f32> {
+    Vector2::new(
+        WIDTH + BORDER * 2. + BAND * 2.,
+        HEIGHT + BORDER * 2. + BAND * 2.,
+    )
+}
+
+fn table_center() -> Vector2<f32> {
+    Vector2::new(MARGIN_LEFT, MARGIN_TOP) + table_size() * 0.5
+}
+
+/// Pinch gestures of the touch screen, turned into a zoom factor.
+#[cfg(target_arch = "wasm32")]
+pub struct Pinch {
+    factor: Rc<Cell<f32>>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Pinch {
+    pub fn new() -> Self {
+        use stdweb::traits::*;
+        use stdweb::web::{
+            document,
+            event::{TouchEnd, TouchMove},
+        };
+
+        let factor = Rc::new(Cell::new(1.));
+        let distance: Rc<Cell<Option<f32>>> = Rc::new(Cell::new(None));
+
+        let move_factor = factor.clone();
+        let move_distance = distance.clone();
+        document().add_event_listener(move |event: TouchMove| {
+            let touches = event.touches();
+            if touches.len() != 2 {
+                move_distance.set(None);
+                return;
+            }
+            let dx = touches[0].client_x() - touches[1].client_x();
+            let dy = touches[0].client_y() - touches[1].client_y();
+            let current = (dx * dx + dy * dy).sqrt() as f32;
+            if let Some(previous) = move_distance.get() {
+                if previous > 0. {
+                    move_factor.set(move_factor.get() * current / previous);
+                }
+            }
+            move_distance.set(Some(current));
+        });
+        document().add_event_listener(move |_: TouchEnd| {
+            distance.set(None);
+        });
+
+        Pinch { factor }
+    }
+
+    /// Zoom factor accumulated since the last call.
+    pub fn take(&self) -> f32 {
+        self.factor.replace(1.)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Pinch {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Pinch {
+    pub fn new() -> Self {
+        Pinch {}
+    }
+
+    pub fn take(&self) -> f32 {
+        1.
+    }
+}
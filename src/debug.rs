@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+
+use ncollide2d::{
+    math::Vector as Vector2,
+    query::ContactManifold,
+    shape::{Ball, Cuboid},
+};
+
+use quicksilver::{
+    geom::{Circle, Rectangle, Transform, Vector},
+    graphics::{Background::Col, Color},
+    lifecycle::Window,
+};
+
+use super::camera::Camera;
+use super::PoolTable;
+
+const FRAME_TIME_SAMPLES: usize = 120;
+const FRAME_TIME_BAR_WIDTH: f32 = 2.;
+// pixels per millisecond
+const FRAME_TIME_SCALE: f32 = 2.;
+const FRAME_TIME_BUDGET: f32 = 1000. / 60.;
+
+// seconds of motion displayed by the velocity vectors
+const VELOCITY_SCALE: f32 = 0.25;
+const VECTOR_WIDTH: f32 = 2.;
+const CONTACT_SIZE: f32 = 3.;
+// length of the contact normals, in world units
+const CONTACT_NORMAL_SIZE: f32 = 600.;
+const ACTIVATION_SIZE: f32 = 3.;
+
+/// Runtime overlay displaying what the physics engine sees.
+pub struct DebugOverlay {
+    frame_times: VecDeque<f32>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        DebugOverlay {
+            frame_times: VecDeque::with_capacity(FRAME_TIME_SAMPLES),
+        }
+    }
+
+    /// Record the duration of the last frame, in milliseconds.
+    pub fn record_frame(&mut self, window: &Window) {
+        let fps = window.current_fps() as f32;
+        if fps <= 0. {
+            return;
+        }
+        if self.frame_times.len() == FRAME_TIME_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(1000. / fps);
+    }
+
+    pub fn draw(&self, window: &mut Window, pool_table: &PoolTable, camera: &Camera) {
+        self.draw_colliders(window, pool_table, camera);
+        self.draw_contacts(window, pool_table, camera);
+        self.draw_balls_state(window, pool_table, camera);
+        self.draw_frame_times(window);
+    }
+
+    fn draw_colliders(&self, window: &mut Window, pool_table: &PoolTable, camera: &Camera) {
+        for collider in pool_table.world.colliders() {
+            let body = collider.data().body();
+            let color = if pool_table.holes.contains(&body) {
+                Color::MAGENTA.with_alpha(0.5)
+            } else if pool_table
+                .bounds
+                .iter()
+                .any(|(_, handle)| *handle == collider.handle())
+            {
+                Color::RED.with_alpha(0.5)
+            } else {
+                Color::CYAN.with_alpha(0.5)
+            };
+
+            let pos = collider.position().translation.vector;
+            let margin = collider.margin();
+
+            if let Some(ball) = collider.shape().as_shape::<Ball<f32>>() {
+                let outer = Circle::new(camera.to_screen(pos), camera.scale(ball.radius() + margin));
+                window.draw(&outer, Col(color));
+            } else if let Some(cuboid) = collider.shape().as_shape::<Cuboid<f32>>() {
+                let half_size = cuboid.half_extents() + Vector2::repeat(margin);
+                let outer = camera.rect(pos - half_size, half_size * 2.);
+                window.draw(&outer, Col(color));
+            }
+        }
+    }
+
+    fn draw_contacts(&self, window: &mut Window, pool_table: &PoolTable, camera: &Camera) {
+        let mut manifolds: Vec<&ContactManifold<f32>> = Vec::new();
+        for (_, _, generator) in pool_table.world.collision_world().contact_pairs() {
+            generator.contacts(&mut manifolds);
+        }
+
+        for manifold in manifolds {
+            for tracked in manifold.contacts() {
+                let contact = &tracked.contact;
+                let world_point = contact.world1.coords;
+                let point = camera.to_screen(world_point);
+                let normal = camera
+                    .to_screen(world_point + contact.normal.unwrap() * CONTACT_NORMAL_SIZE)
+                    - point;
+                self.draw_vector(window, point, normal, Color::ORANGE);
+                window.draw(
+                    &Rectangle::new_sized((CONTACT_SIZE * 2., CONTACT_SIZE * 2.)).with_center(point),
+                    Col(Color::ORANGE),
+                );
+            }
+        }
+    }
+
+    fn draw_balls_state(&self, window: &mut Window, pool_table: &PoolTable, camera: &Camera) {
+        for ball in pool_table.balls() {
+            let body = pool_table.world.rigid_body(ball).unwrap();
+            let world_center = body.position().translation.vector;
+            let center = camera.to_screen(world_center);
+            let velocity =
+                camera.to_screen(world_center + body.velocity().linear * VELOCITY_SCALE) - center;
+            self.draw_vector(window, center, velocity, Color::BLUE);
+
+            let activation = if body.is_active() {
+                Color::GREEN
+            } else {
+                Color::BLACK.with_alpha(0.5)
+            };
+            window.draw(
+                &Rectangle::new_sized((ACTIVATION_SIZE * 2., ACTIVATION_SIZE * 2.))
+                    .with_center(center),
+                Col(activation),
+            );
+        }
+    }
+
+    fn draw_vector(&self, window: &mut Window, origin: Vector, vector: Vector, color: Color) {
+        let length = vector.len();
+        if length < 1. {
+            return;
+        }
+        let rect = Rectangle::new_sized((length, VECTOR_WIDTH)).with_center(origin + vector * 0.5);
+        window.draw_ex(
+            &rect,
+            Col(color),
+            Transform::rotate(vector.angle()),
+            1,
+        );
+    }
+
+    fn draw_frame_times(&self, window: &mut Window) {
+        let screen = window.screen_size();
+        let bottom = screen.y - 10.;
+        let left = 10.;
+
+        let width = FRAME_TIME_BAR_WIDTH * FRAME_TIME_SAMPLES as f32;
+        let budget = FRAME_TIME_BUDGET * FRAME_TIME_SCALE;
+        window.draw(
+            &Rectangle::new((left, bottom - budget * 2.), (width, budget * 2.)),
+            Col(Color::BLACK.with_alpha(0.5)),
+        );
+        // the line of a 60fps frame.
+        window.draw(
+            &Rectangle::new((left, bottom - budget), (width, 1.)),
+            Col(Color::WHITE),
+        );
+
+        for (idx, frame_time) in self.frame_times.iter().enumerate() {
+            let height = (frame_time * FRAME_TIME_SCALE).min(budget * 2.);
+            let color = if *frame_time > FRAME_TIME_BUDGET * 1.1 {
+                Color::RED
+            } else {
+                Color::GREEN
+            };
+            window.draw(
+                &Rectangle::new(
+                    (left + idx as f32 * FRAME_TIME_BAR_WIDTH, bottom - height),
+                    (FRAME_TIME_BAR_WIDTH, height),
+                ),
+                Col(color),
+            );
+        }
+    }
+}
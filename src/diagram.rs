@@ -0,0 +1,572 @@
+//! Diagrams of a table, drawn to SVG or to an RGBA image without a window.
+//!
+//! A diagram shows the balls, and optionally the shot of the cue ball, the
+//! paths of the balls predicted by a headless play of the shot, and some
+//! highlighted holes. It uses the colors of the game.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+
+use image::png::PNGEncoder;
+use image::ColorType;
+use nalgebra::UnitQuaternion;
+
+use super::physics::PhysicsProfile;
+use super::shot::Shot;
+use super::simulation;
+use super::sprite::{self, BallStyle, Rgb};
+use super::state::{table_position, BallState, TableState};
+use super::{
+    BallKind, GameMode, PoolTable, BAND, BORDER, HEIGHT, HOLE_SIZE, MAX_FORCE, WIDTH,
+};
+
+const BACKGROUND_COLOR: Rgb = [0xcc, 0xcc, 0xcc];
+const SHOT_COLOR: Rgb = [0x1c, 0x3f, 0xff];
+const HIGHLIGHT_COLOR: Rgb = [0xff, 0xe0, 0x40];
+const OUTLINE_COLOR: Rgb = [0x11, 0x11, 0x11];
+
+// length of the shot arrow at the maximum force, in table units
+const SHOT_ARROW_SIZE: f32 = 3000.;
+const SHOT_ARROW_WIDTH: f32 = 50.;
+const PATH_WIDTH: f32 = 40.;
+const HIGHLIGHT_WIDTH: f32 = 70.;
+// a position of the predicted paths every PATH_SAMPLE steps
+const PATH_SAMPLE: usize = 3;
+
+// the diagram covers the whole table, borders included
+const ORIGIN: f32 = -BORDER;
+const FULL_WIDTH: f32 = WIDTH + BAND * 2. + BORDER * 2.;
+const FULL_HEIGHT: f32 = HEIGHT + BAND * 2. + BORDER * 2.;
+
+/// A drawing of the table, in table coordinates.
+#[derive(Clone, Debug)]
+pub struct Diagram {
+    mode: GameMode,
+    profile: PhysicsProfile,
+    balls: Vec<BallState>,
+    holes: Vec<(f32, f32)>,
+    shot: Option<Shot>,
+    paths: Vec<(BallKind, Vec<(f32, f32)>)>,
+    highlights: Vec<usize>,
+}
+
+impl Diagram {
+    pub fn of(pool_table: &PoolTable) -> Self {
+        let holes = pool_table
+            .holes
+            .iter()
+            .map(|hole| {
+                let pos = pool_table.world.body_part(*hole).position().translation.vector;
+                table_position(pos)
+            })
+            .collect();
+        Diagram {
+            mode: pool_table.mode,
+            profile: *pool_table.profile(),
+            balls: TableState::of(pool_table).balls,
+            holes,
+            shot: None,
+            paths: Vec::new(),
+            highlights: Vec::new(),
+        }
+    }
+
+    pub fn of_state(state: &TableState) -> Self {
+        Diagram::of(&state.build())
+    }
+
+    /// Draw the shot as an arrow from the cue ball.
+    pub fn with_shot(mut self, shot: Shot) -> Self {
+        self.shot = Some(shot);
+        self
+    }
+
+    /// Play the shot on a copy of the table and draw the path of every
+    /// ball that moved; the holes where balls fell are highlighted.
+    pub fn with_predicted_paths(mut self, shot: &Shot) -> Self {
+        let state = TableState {
+            mode: self.mode,
+            balls: self.balls.clone(),
+            profile: self.profile,
+        };
+        let mut pool_table = state.build();
+        let kinds: HashMap<_, _> = pool_table
+            .balls()
+            .into_iter()
+            .filter_map(|ball| Some((ball, pool_table.ball_kind(ball)?)))
+            .collect();
+        // same order as the balls, so the diagram does not change between runs
+        let order = pool_table.balls();
+
+        let mut paths: HashMap<_, Vec<(f32, f32)>> = HashMap::new();
+        let mut record = |pool_table: &PoolTable| {
+            for ball in pool_table.balls() {
+                let pos = pool_table.world.body_part(ball).position().translation.vector;
+                paths.entry(ball).or_insert_with(Vec::new).push(table_position(pos));
+            }
+        };
+        record(&pool_table);
+        simulation::play(&mut pool_table, shot, |step, pool_table, _| {
+            if step % PATH_SAMPLE == 0 || !pool_table.has_force() {
+                record(pool_table);
+            }
+        });
+
+        for pocketed in pool_table.pocketed_balls() {
+            if let Some(hole) = pocketed.hole {
+                if let Some(path) = paths.get_mut(&pocketed.ball) {
+                    path.push(self.holes[hole]);
+                }
+                self = self.with_highlight(hole);
+            }
+        }
+        let radius = self.profile.ball_radius;
+        for ball in order {
+            let path = match paths.remove(&ball) {
+                Some(path) => path,
+                None => continue,
+            };
+            let (x0, y0) = path[0];
+            let moved = path
+                .iter()
+                .any(|(x, y)| (x - x0).hypot(y - y0) > radius * 0.5);
+            if moved {
+                self.paths.push((kinds[&ball], path));
+            }
+        }
+        self
+    }
+
+    /// Highlight a hole, by its index in the order of the table.
+    pub fn with_highlight(mut self, hole: usize) -> Self {
+        if hole < self.holes.len() && !self.highlights.contains(&hole) {
+            self.highlights.push(hole);
+        }
+        self
+    }
+
+    /// Height of the image for a width, in pixels.
+    pub fn height(width: u32) -> u32 {
+        (width as f32 * FULL_HEIGHT / FULL_WIDTH).round() as u32
+    }
+
+    /// The diagram as an SVG document, `width` pixels wide.
+    pub fn to_svg(&self, width: u32) -> String {
+        let mut svg = String::new();
+        // writing to a String does not fail
+        self.write_svg(&mut svg, width).unwrap();
+        svg
+    }
+
+    fn write_svg(&self, svg: &mut String, width: u32) -> std::fmt::Result {
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            width,
+            Diagram::height(width),
+            ORIGIN,
+            ORIGIN,
+            FULL_WIDTH,
+            FULL_HEIGHT
+        )?;
+        writeln!(
+            svg,
+            r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M0,0 L10,5 L0,10 z" fill="{}"/></marker></defs>"#,
+            hex(SHOT_COLOR)
+        )?;
+
+        let rect = |svg: &mut String, x: f32, y: f32, w: f32, h: f32, color: Rgb| {
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x,
+                y,
+                w,
+                h,
+                hex(color)
+            )
+        };
+        rect(svg, ORIGIN, ORIGIN, FULL_WIDTH, FULL_HEIGHT, sprite::BORDER_COLOR)?;
+        rect(svg, 0., 0., WIDTH + BAND * 2., HEIGHT + BAND * 2., sprite::BAND_COLOR)?;
+        rect(svg, BAND, BAND, WIDTH, HEIGHT, sprite::CLOTH_COLOR)?;
+
+        for (index, (x, y)) in self.holes.iter().enumerate() {
+            write!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}""#,
+                x,
+                y,
+                HOLE_SIZE,
+                hex(sprite::HOLE_COLOR)
+            )?;
+            if self.highlights.contains(&index) {
+                write!(
+                    svg,
+                    r#" stroke="{}" stroke-width="{}""#,
+                    hex(HIGHLIGHT_COLOR),
+                    HIGHLIGHT_WIDTH
+                )?;
+            }
+            writeln!(svg, "/>")?;
+        }
+
+        for (kind, path) in self.paths.iter() {
+            let points: Vec<String> = path
+                .iter()
+                .map(|(x, y)| format!("{:.0},{:.0}", x, y))
+                .collect();
+            writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-dasharray="{} {}" stroke-linejoin="round" opacity="0.8"/>"#,
+                points.join(" "),
+                hex(path_color(*kind)),
+                PATH_WIDTH,
+                PATH_WIDTH * 3.,
+                PATH_WIDTH * 2.
+            )?;
+        }
+
+        let radius = self.profile.ball_radius;
+        for ball in self.balls.iter() {
+            let style = BallStyle::of(ball.kind);
+            let (x, y) = (ball.x, ball.y);
+            if style.stripe {
+                writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="white"/>"#,
+                    x, y, radius
+                )?;
+                // the stripe is inscribed in the ball
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    x - radius * 0.866,
+                    y - radius * 0.5,
+                    radius * 1.732,
+                    radius,
+                    hex(style.color)
+                )?;
+            } else {
+                writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                    x,
+                    y,
+                    radius,
+                    hex(style.color)
+                )?;
+            }
+            writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                x,
+                y,
+                radius,
+                hex(OUTLINE_COLOR),
+                radius * 0.06
+            )?;
+            if let Some(number) = style.number {
+                writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="white"/>"#,
+                    x,
+                    y,
+                    radius * 0.5
+                )?;
+                writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    x,
+                    y,
+                    radius * 0.6,
+                    number
+                )?;
+            }
+        }
+
+        if let Some((start, end)) = self.shot_arrow() {
+            writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" marker-end="url(#arrow)"/>"#,
+                start.0,
+                start.1,
+                end.0,
+                end.1,
+                hex(SHOT_COLOR),
+                SHOT_ARROW_WIDTH
+            )?;
+        }
+        writeln!(svg, "</svg>")
+    }
+
+    /// The diagram rasterized to RGBA pixels, `width` pixels wide.
+    /// Return the width, the height and the pixels.
+    pub fn to_rgba(&self, width: u32) -> (u32, u32, Vec<u8>) {
+        let height = Diagram::height(width);
+        let mut canvas = Canvas::new(width, height, width as f32 / FULL_WIDTH);
+
+        canvas.fill_rect((ORIGIN, ORIGIN), (FULL_WIDTH, FULL_HEIGHT), sprite::BORDER_COLOR);
+        canvas.fill_rect((0., 0.), (WIDTH + BAND * 2., HEIGHT + BAND * 2.), sprite::BAND_COLOR);
+        canvas.fill_rect((BAND, BAND), (WIDTH, HEIGHT), sprite::CLOTH_COLOR);
+
+        for (index, hole) in self.holes.iter().enumerate() {
+            if self.highlights.contains(&index) {
+                canvas.fill_circle(*hole, HOLE_SIZE + HIGHLIGHT_WIDTH * 0.5, HIGHLIGHT_COLOR, 1.);
+                canvas.fill_circle(*hole, HOLE_SIZE - HIGHLIGHT_WIDTH * 0.5, sprite::HOLE_COLOR, 1.);
+            } else {
+                canvas.fill_circle(*hole, HOLE_SIZE, sprite::HOLE_COLOR, 1.);
+            }
+        }
+
+        for (kind, path) in self.paths.iter() {
+            for segment in path.windows(2) {
+                canvas.line(segment[0], segment[1], PATH_WIDTH, path_color(*kind), 0.8);
+            }
+        }
+
+        let radius = self.profile.ball_radius;
+        let size = (radius * 2. * canvas.scale).round().max(1.) as u32;
+        for ball in self.balls.iter() {
+            let sprite = sprite::ball(size, &BallStyle::of(ball.kind), &UnitQuaternion::identity());
+            canvas.blit((ball.x - radius, ball.y - radius), size, &sprite);
+        }
+
+        if let Some((start, end)) = self.shot_arrow() {
+            canvas.line(start, end, SHOT_ARROW_WIDTH, SHOT_COLOR, 1.);
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let length = dx.hypot(dy).max(1.);
+            let (ux, uy) = (dx / length, dy / length);
+            let head = SHOT_ARROW_WIDTH * 4.;
+            for side in [-1., 1.].iter() {
+                let tip = (
+                    end.0 - (ux + uy * side) * head,
+                    end.1 - (uy - ux * side) * head,
+                );
+                canvas.line(end, tip, SHOT_ARROW_WIDTH, SHOT_COLOR, 1.);
+            }
+        }
+
+        (width, height, canvas.data)
+    }
+
+    /// The diagram as a PNG image, `width` pixels wide.
+    pub fn to_png(&self, width: u32) -> io::Result<Vec<u8>> {
+        let (width, height, data) = self.to_rgba(width);
+        let mut png = Vec::new();
+        PNGEncoder::new(&mut png).encode(&data, width, height, ColorType::RGBA(8))?;
+        Ok(png)
+    }
+
+    // the arrow of the shot, from the edge of the cue ball
+    fn shot_arrow(&self) -> Option<((f32, f32), (f32, f32))> {
+        let shot = self.shot?;
+        let white = self.balls.iter().find(|ball| ball.kind == BallKind::White)?;
+        let direction = shot.direction();
+        let radius = self.profile.ball_radius;
+        let length = SHOT_ARROW_SIZE * (shot.force / MAX_FORCE).min(1.);
+        let start = (
+            white.x + direction.x * radius,
+            white.y + direction.y * radius,
+        );
+        let end = (
+            start.0 + direction.x * length,
+            start.1 + direction.y * length,
+        );
+        Some((start, end))
+    }
+}
+
+// the cue ball is white, its path would not show on a light background
+fn path_color(kind: BallKind) -> Rgb {
+    match kind {
+        BallKind::White => [0xff, 0xff, 0xff],
+        kind => BallStyle::of(kind).color,
+    }
+}
+
+fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+// RGBA pixels, drawn in table coordinates.
+struct Canvas {
+    width: u32,
+    height: u32,
+    // pixels per table unit
+    scale: f32,
+    data: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, scale: f32) -> Self {
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for _ in 0..width * height {
+            data.extend_from_slice(&BACKGROUND_COLOR);
+            data.push(0xff);
+        }
+        Canvas {
+            width,
+            height,
+            scale,
+            data,
+        }
+    }
+
+    fn to_pixel(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        ((x - ORIGIN) * self.scale, (y - ORIGIN) * self.scale)
+    }
+
+    // blend a color over a pixel, with a coverage in [0, 1]
+    fn blend(&mut self, px: i64, py: i64, color: Rgb, alpha: f32) {
+        if px < 0 || py < 0 || px >= self.width as i64 || py >= self.height as i64 || alpha <= 0. {
+            return;
+        }
+        let idx = ((py as u32 * self.width + px as u32) * 4) as usize;
+        let alpha = alpha.min(1.);
+        for channel in 0..3 {
+            let under = self.data[idx + channel] as f32;
+            self.data[idx + channel] = (under + (color[channel] as f32 - under) * alpha) as u8;
+        }
+    }
+
+    // visit the pixels of a box in pixel coordinates, with the distance
+    // from their center to the shape
+    fn fill<F>(&mut self, min: (f32, f32), max: (f32, f32), color: Rgb, alpha: f32, distance: F)
+    where
+        F: Fn(f32, f32) -> f32,
+    {
+        let (x0, y0) = (min.0.floor().max(0.) as i64, min.1.floor().max(0.) as i64);
+        let (x1, y1) = (max.0.ceil() as i64, max.1.ceil() as i64);
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                // antialiased edge
+                let coverage = (0.5 - distance(px as f32 + 0.5, py as f32 + 0.5)).max(0.).min(1.);
+                self.blend(px, py, color, coverage * alpha);
+            }
+        }
+    }
+
+    fn fill_rect(&mut self, pos: (f32, f32), size: (f32, f32), color: Rgb) {
+        let min = self.to_pixel(pos);
+        let max = self.to_pixel((pos.0 + size.0, pos.1 + size.1));
+        self.fill(min, max, color, 1., |x, y| {
+            (min.0 - x).max(x - max.0).max(min.1 - y).max(y - max.1)
+        });
+    }
+
+    fn fill_circle(&mut self, center: (f32, f32), radius: f32, color: Rgb, alpha: f32) {
+        let (cx, cy) = self.to_pixel(center);
+        let r = radius * self.scale;
+        self.fill((cx - r - 1., cy - r - 1.), (cx + r + 1., cy + r + 1.), color, alpha, |x, y| {
+            (x - cx).hypot(y - cy) - r
+        });
+    }
+
+    fn line(&mut self, start: (f32, f32), end: (f32, f32), width: f32, color: Rgb, alpha: f32) {
+        let (ax, ay) = self.to_pixel(start);
+        let (bx, by) = self.to_pixel(end);
+        // at least one pixel wide, so thin lines do not vanish
+        let half = (width * self.scale * 0.5).max(0.5);
+        let (dx, dy) = (bx - ax, by - ay);
+        let length2 = (dx * dx + dy * dy).max(1e-6);
+        let min = (ax.min(bx) - half - 1., ay.min(by) - half - 1.);
+        let max = (ax.max(bx) + half + 1., ay.max(by) + half + 1.);
+        self.fill(min, max, color, alpha, |x, y| {
+            let t = (((x - ax) * dx + (y - ay) * dy) / length2).max(0.).min(1.);
+            (x - ax - dx * t).hypot(y - ay - dy * t) - half
+        });
+    }
+
+    // draw an RGBA sprite of size x size pixels, its top left corner at pos
+    fn blit(&mut self, pos: (f32, f32), size: u32, sprite: &[u8]) {
+        let (x0, y0) = self.to_pixel(pos);
+        let (x0, y0) = (x0.round() as i64, y0.round() as i64);
+        for sy in 0..size {
+            for sx in 0..size {
+                let idx = ((sy * size + sx) * 4) as usize;
+                let color = [sprite[idx], sprite[idx + 1], sprite[idx + 2]];
+                let alpha = sprite[idx + 3] as f32 / 255.;
+                self.blend(x0 + sx as i64, y0 + sy as i64, color, alpha);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ncollide2d::math::Vector as Vector2;
+
+    fn state() -> TableState {
+        TableState {
+            mode: GameMode::Practice,
+            balls: vec![
+                BallState {
+                    kind: BallKind::White,
+                    x: 3880.,
+                    y: 4000.,
+                },
+                BallState {
+                    kind: BallKind::Numbered(12),
+                    x: 11520.,
+                    y: 4000.,
+                },
+            ],
+            profile: PhysicsProfile::standard(),
+        }
+    }
+
+    fn shot() -> Shot {
+        Shot {
+            angle: 0.,
+            force: 700.,
+            spin: Vector2::new(0., 0.),
+            elevation: 0.,
+        }
+    }
+
+    #[test]
+    fn the_svg_shows_the_balls_and_the_shot() {
+        let svg = Diagram::of_state(&state()).with_shot(shot()).to_svg(1024);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1024""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        let white = hex(BallStyle::of(BallKind::White).color);
+        assert!(svg.contains(&format!(
+            r#"<circle cx="3880" cy="4000" r="240" fill="{}"/>"#,
+            white
+        )));
+        assert!(svg.contains(r#"<circle cx="11520" cy="4000" r="240" fill="white"/>"#));
+        assert!(svg.contains(">12</text>"));
+        // from the edge of the cue ball, half of the length at half the force
+        assert!(svg.contains(r#"<line x1="4120" y1="4000" x2="5620" y2="4000""#));
+    }
+
+    #[test]
+    fn the_predicted_paths_reach_the_object_ball() {
+        let svg = Diagram::of_state(&state())
+            .with_predicted_paths(&shot())
+            .to_svg(1024);
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+
+    #[test]
+    fn the_png_has_the_size_of_the_diagram() {
+        let diagram = Diagram::of_state(&state()).with_shot(shot());
+        let (width, height, data) = diagram.to_rgba(640);
+        assert_eq!((width, height), (640, Diagram::height(640)));
+        assert_eq!(data.len(), (width * height * 4) as usize);
+
+        let png = diagram.to_png(640).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // the size is the start of the header chunk, big endian
+        let size = |at: usize| {
+            u32::from(png[at]) << 24
+                | u32::from(png[at + 1]) << 16
+                | u32::from(png[at + 2]) << 8
+                | u32::from(png[at + 3])
+        };
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!((size(16), size(20)), (640, Diagram::height(640)));
+    }
+}
@@ -0,0 +1,269 @@
+//! Practice drills, a starting layout and a goal to reach in one shot.
+//!
+//! The drills are read from `static/drills.json`, a list of drills like:
+//!
+//! ```json
+//! {
+//!     "name": "Straight in",
+//!     "table": {"mode": "practice", "balls": [
+//!         {"kind": "white", "x": 4000, "y": 4000},
+//!         {"kind": "yellow", "x": 12000, "y": 1200}
+//!     ]},
+//!     "goals": [
+//!         {"type": "pot", "ball": "yellow", "hole": "top_right"}
+//!     ]
+//! }
+//! ```
+//!
+//! Positions are relative to the top left corner of the table.
+
+use std::collections::HashMap;
+use std::io::{Error as IOError, ErrorKind};
+
+use serde_derive::Deserialize;
+
+use quicksilver::{
+    geom::{Rectangle, Vector},
+    graphics::{
+        Background::{Col, Img},
+        Color, Font, FontStyle,
+    },
+    lifecycle::{Asset, Window},
+    load_file,
+    saving::{load, save},
+    Future, Result,
+};
+
+use super::state::{table_position, TableState};
+use super::{BallKind, PoolTable};
+
+pub const DRILLS_FILE: &str = "drills.json";
+
+const APP_NAME: &str = "wasm-pool";
+const RECORDS_PROFILE: &str = "drills";
+
+const FONT_SIZE: f32 = 20.;
+const LINE_HEIGHT: f32 = 28.;
+const MARGIN: f32 = 10.;
+
+/// The holes, in the order of the table.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hole {
+    TopLeft,
+    Top,
+    TopRight,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+}
+
+impl Hole {
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Hole::TopLeft => "top left",
+            Hole::Top => "top",
+            Hole::TopRight => "top right",
+            Hole::BottomRight => "bottom right",
+            Hole::Bottom => "bottom",
+            Hole::BottomLeft => "bottom left",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Goal {
+    /// Pot a ball, in a given hole or in any of them.
+    Pot {
+        ball: BallKind,
+        #[serde(default)]
+        hole: Option<Hole>,
+    },
+    /// Leave the cue ball inside a rectangle of the table.
+    CueBallInZone {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+}
+
+impl Goal {
+    fn is_achieved(&self, pool_table: &PoolTable) -> bool {
+        match self {
+            Goal::Pot { ball, hole } => pool_table.pocketed_balls.iter().any(|pocketed| {
+                pocketed.kind == *ball
+                    && pocketed.hole.is_some()
+                    && hole.map_or(true, |hole| pocketed.hole == Some(hole.index()))
+            }),
+            Goal::CueBallInZone {
+                x,
+                y,
+                width,
+                height,
+            } => match pool_table.white_ball_handle {
+                Some(ball) => {
+                    let pos = pool_table.world.body_part(ball).position().translation.vector;
+                    let (pos_x, pos_y) = table_position(pos);
+                    pos_x >= *x && pos_x <= x + width && pos_y >= *y && pos_y <= y + height
+                }
+                None => false,
+            },
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Goal::Pot { ball, hole: Some(hole) } => {
+                format!("pot the {} in the {} hole", ball_name(*ball), hole.name())
+            }
+            Goal::Pot { ball, hole: None } => format!("pot the {}", ball_name(*ball)),
+            Goal::CueBallInZone { .. } => "leave the cue ball in the zone".to_string(),
+        }
+    }
+}
+
+fn ball_name(kind: BallKind) -> String {
+    match kind {
+        BallKind::White => "cue ball".to_string(),
+        BallKind::Black => "black".to_string(),
+        BallKind::Red => "red".to_string(),
+        BallKind::Yellow => "yellow".to_string(),
+        BallKind::Numbered(number) => format!("{} ball", number),
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct Drill {
+    pub name: String,
+    pub table: TableState,
+    // all of them must be achieved by the same shot
+    pub goals: Vec<Goal>,
+}
+
+impl Drill {
+    /// The goals are reached, and the cue ball is still on the table.
+    pub fn is_achieved(&self, pool_table: &PoolTable) -> bool {
+        pool_table.white_ball_handle.is_some()
+            && self.goals.iter().all(|goal| goal.is_achieved(pool_table))
+    }
+
+    pub fn description(&self) -> String {
+        let goals: Vec<String> = self.goals.iter().map(|goal| goal.description()).collect();
+        goals.join(", ")
+    }
+}
+
+/// Load the drill library.
+pub fn load_drills() -> impl Future<Item = Vec<Drill>, Error = quicksilver::Error> {
+    load_file(DRILLS_FILE).and_then(|bytes| {
+        serde_json::from_slice(&bytes)
+            .map_err(|err| IOError::new(ErrorKind::InvalidData, err).into())
+    })
+}
+
+/// The attempts at a drill, until it succeeds.
+pub struct DrillSession {
+    pub drill: Drill,
+    pub attempts: u32,
+    // attempts of the last success
+    pub succeeded: Option<u32>,
+    pub new_best: bool,
+}
+
+impl DrillSession {
+    pub fn new(drill: Drill) -> Self {
+        DrillSession {
+            drill,
+            attempts: 0,
+            succeeded: None,
+            new_best: false,
+        }
+    }
+
+    /// Score the shot that has been played, the attempts start again
+    /// after a success.
+    pub fn shot_complete(&mut self, pool_table: &PoolTable, records: &mut Records) {
+        self.attempts += 1;
+        if self.drill.is_achieved(pool_table) {
+            info!("Drill {} done in {} attempts", self.drill.name, self.attempts);
+            self.new_best = records.record(&self.drill.name, self.attempts);
+            self.succeeded = Some(self.attempts);
+            self.attempts = 0;
+        }
+    }
+
+    /// Draw the goal and the score, at the bottom of the screen.
+    pub fn draw(
+        &self,
+        window: &mut Window,
+        font: &mut Asset<Font>,
+        records: &Records,
+    ) -> Result<()> {
+        let best = match records.best(&self.drill.name) {
+            Some(best) => format!("best: {}", best),
+            None => "best: -".to_string(),
+        };
+        let mut lines = vec![
+            format!("{}: {}", self.drill.name, self.drill.description()),
+            format!("Attempt {}, {}", self.attempts + 1, best),
+        ];
+        if let Some(attempts) = self.succeeded {
+            let record = if self.new_best { ", new personal best!" } else { "" };
+            lines.push(format!("Success in {} attempts{}", attempts, record));
+        }
+
+        let screen = window.screen_size();
+        let height = LINE_HEIGHT * lines.len() as f32;
+        let top = screen.y - MARGIN - height;
+        window.draw(
+            &Rectangle::new((0., top), (screen.x, height)),
+            Col(Color::BLACK.with_alpha(0.5)),
+        );
+        font.execute(|font| {
+            let style = FontStyle::new(FONT_SIZE, Color::WHITE);
+            for (idx, line) in lines.iter().enumerate() {
+                let image = font.render(line, &style)?;
+                let center = Vector::new(screen.x / 2., top + LINE_HEIGHT * (idx as f32 + 0.5));
+                window.draw(&image.area().with_center(center), Img(&image));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Personal bests, the fewest attempts to succeed every drill.
+pub struct Records {
+    best: HashMap<String, u32>,
+}
+
+impl Records {
+    pub fn load() -> Self {
+        let best = load(APP_NAME, RECORDS_PROFILE).unwrap_or_else(|err| {
+            info!("No drill records: {}", err);
+            HashMap::new()
+        });
+        Records { best }
+    }
+
+    pub fn best(&self, drill: &str) -> Option<u32> {
+        self.best.get(drill).cloned()
+    }
+
+    /// Record a success, return true on a new personal best.
+    pub fn record(&mut self, drill: &str, attempts: u32) -> bool {
+        if self.best(drill).map_or(false, |best| best <= attempts) {
+            return false;
+        }
+        self.best.insert(drill.to_string(), attempts);
+        if let Err(err) = save(APP_NAME, RECORDS_PROFILE, &self.best) {
+            warn!("Unable to save the drill records: {}", err);
+        }
+        true
+    }
+}
@@ -0,0 +1,357 @@
+//! Editor of the table layout, to set up arbitrary positions.
+//!
+//! The balls are dragged with the left button, the right button adds a
+//! ball where there is none and removes the one under the pointer.
+
+use ncollide2d::math::{Isometry as Isometry2, Vector as Vector2};
+use nphysics2d::object::BodyHandle;
+
+use quicksilver::{
+    geom::{Circle, Rectangle, Vector},
+    graphics::{
+        Background::{Col, Img},
+        Color, Font, FontStyle,
+    },
+    input::{ButtonState, Key, MouseButton},
+    lifecycle::{Asset, Window},
+    Result,
+};
+
+use super::camera::Camera;
+use super::notation::Position;
+use super::state::{table_position, TableState, REST_TOLERANCE};
+use super::text_field;
+use super::{
+    BallKind, PoolTable, BAND, BORDER, HEIGHT, MARGIN_LEFT, MARGIN_TOP, WIDTH,
+};
+
+const GRID_LINE_WIDTH: f32 = 1.;
+const SPOT_SIZE: f32 = 3.;
+
+const FONT_SIZE: f32 = 18.;
+const LINE_HEIGHT: f32 = 24.;
+const MARGIN: f32 = 10.;
+
+/// What the game has to do after the editor has been updated.
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditorAction {
+    Play,
+}
+
+/// The ball kinds that can be added, in the order of the Tab key.
+fn ball_kinds() -> Vec<BallKind> {
+    let mut kinds = vec![BallKind::White, BallKind::Black, BallKind::Red, BallKind::Yellow];
+    kinds.extend((1..16).map(BallKind::Numbered));
+    kinds
+}
+
+// the grid is aligned on the corner of the cloth, a line every ball radius
+fn grid_size(pool_table: &PoolTable) -> f32 {
+    pool_table.profile().ball_radius
+}
+
+/// The head, center and foot spots, in world coordinates, the rack
+/// of the game uses the same ones.
+fn spots() -> Vec<Vector2<f32>> {
+    let y = MARGIN_TOP + BORDER + HEIGHT * 0.5;
+    [0.25, 0.5, 0.75]
+        .iter()
+        .map(|ratio| Vector2::new(MARGIN_LEFT + BORDER + WIDTH * ratio, y))
+        .collect()
+}
+
+struct Drag {
+    ball: BodyHandle,
+    // position before the drag, restored if the drop is rejected
+    origin: Vector2<f32>,
+}
+
+pub struct Editor {
+    kind: usize,
+    grid: bool,
+    drag: Option<Drag>,
+    // the last ball moved or added, for the spots
+    selected: Option<BodyHandle>,
+    message: String,
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Editor {
+            kind: 0,
+            grid: false,
+            drag: None,
+            selected: None,
+            message: String::new(),
+        }
+    }
+
+    /// A ball is being moved, the mouse must not move the view.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    pub fn update(
+        &mut self,
+        window: &mut Window,
+        pool_table: &mut PoolTable,
+        camera: &Camera,
+    ) -> Option<EditorAction> {
+        let pos = self.snap(pool_table, camera.to_world(window.mouse().pos()));
+        let hovered = self.ball_at(pool_table, camera.to_world(window.mouse().pos()));
+
+        match window.mouse()[MouseButton::Left] {
+            ButtonState::Pressed => {
+                if let Some(ball) = hovered {
+                    let origin = pool_table.world.body_part(ball).position().translation.vector;
+                    self.drag = Some(Drag { ball, origin });
+                    self.selected = Some(ball);
+                }
+            }
+            ButtonState::Held => {
+                if let Some(ref drag) = self.drag {
+                    move_ball(pool_table, drag.ball, pos);
+                }
+            }
+            ButtonState::Released => {
+                if let Some(drag) = self.drag.take() {
+                    if let Some(overlap) = pool_table.overlap(pos, Some(drag.ball)) {
+                        self.message = format!("Rejected, the ball would be {}", overlap.name());
+                        move_ball(pool_table, drag.ball, drag.origin);
+                    } else {
+                        move_ball(pool_table, drag.ball, pos);
+                    }
+                }
+            }
+            ButtonState::NotPressed => {}
+        }
+
+        if window.mouse()[MouseButton::Right] == ButtonState::Pressed && self.drag.is_none() {
+            match hovered {
+                Some(ball) => {
+                    pool_table.remove_ball(ball);
+                    if self.selected == Some(ball) {
+                        self.selected = None;
+                    }
+                }
+                None => self.add_ball(pool_table, pos),
+            }
+        }
+
+        let keyboard = window.keyboard();
+        if keyboard[Key::Tab] == ButtonState::Pressed {
+            self.kind = (self.kind + 1) % ball_kinds().len();
+        }
+        if keyboard[Key::G] == ButtonState::Pressed {
+            self.grid = !self.grid;
+        }
+        if keyboard[Key::S] == ButtonState::Pressed {
+            self.put_on_spot(pool_table);
+        }
+        if keyboard[Key::C] == ButtonState::Pressed {
+            for ball in pool_table.balls() {
+                pool_table.remove_ball(ball);
+            }
+            self.selected = None;
+        }
+        if keyboard[Key::E] == ButtonState::Pressed {
+            self.export(pool_table);
+        }
+        if keyboard[Key::I] == ButtonState::Pressed {
+            self.import(pool_table);
+        }
+        if keyboard[Key::Return] == ButtonState::Pressed {
+            if pool_table.white_ball_handle.is_none() {
+                self.message = "Place the cue ball before playing".to_string();
+            } else {
+                return Some(EditorAction::Play);
+            }
+        }
+        None
+    }
+
+    fn snap(&self, pool_table: &PoolTable, pos: Vector2<f32>) -> Vector2<f32> {
+        if !self.grid {
+            return pos;
+        }
+        let (x, y) = table_position(pos);
+        let size = grid_size(pool_table);
+        let snap = |value: f32| (value / size).round() * size;
+        pos + Vector2::new(snap(x) - x, snap(y) - y)
+    }
+
+    fn ball_at(&self, pool_table: &PoolTable, pos: Vector2<f32>) -> Option<BodyHandle> {
+        pool_table.balls().into_iter().find(|ball| {
+            let center = pool_table.world.body_part(*ball).position().translation.vector;
+            (center - pos).norm() < pool_table.profile().ball_radius
+        })
+    }
+
+    fn add_ball(&mut self, pool_table: &mut PoolTable, pos: Vector2<f32>) {
+        let kind = ball_kinds()[self.kind];
+        // a single cue ball and a single black
+        let replaced = match kind {
+            BallKind::White => pool_table.white_ball_handle,
+            BallKind::Black => pool_table.ball_8_handle,
+            _ => None,
+        };
+        if let Some(overlap) = pool_table.overlap(pos, replaced) {
+            self.message = format!("Rejected, the ball would be {}", overlap.name());
+            return;
+        }
+        if let Some(ball) = replaced {
+            pool_table.remove_ball(ball);
+        }
+        self.selected = Some(pool_table.place_ball(kind, pos.x, pos.y));
+    }
+
+    /// Move the selected ball to the nearest free spot.
+    fn put_on_spot(&mut self, pool_table: &mut PoolTable) {
+        let ball = match self.selected {
+            Some(ball) => ball,
+            None => return,
+        };
+        let pos = pool_table.world.body_part(ball).position().translation.vector;
+        let mut spots = spots();
+        spots.sort_by(|a, b| (a - pos).norm().partial_cmp(&(b - pos).norm()).unwrap());
+        match spots
+            .into_iter()
+            .find(|spot| pool_table.overlap(*spot, Some(ball)).is_none())
+        {
+            Some(spot) => move_ball(pool_table, ball, spot),
+            None => self.message = "No free spot".to_string(),
+        }
+    }
+
+    /// Show the layout in the position notation, to be copied.
+    fn export(&mut self, pool_table: &PoolTable) {
+        let position = Position {
+            table: TableState::of(pool_table),
+            current_player: 0,
+            groups: None,
+        };
+        text_field::show("Copy the layout:", &position.to_string());
+        self.message = if text_field::CAN_ASK {
+            "Layout exported".to_string()
+        } else {
+            "Layout exported to the log".to_string()
+        };
+    }
+
+    /// Read a layout pasted by the player, in the position notation or as
+    /// the JSON of a `TableState`.
+    fn import(&mut self, pool_table: &mut PoolTable) {
+        if !text_field::CAN_ASK {
+            self.message = "Import is only available in the browser".to_string();
+            return;
+        }
+        let text = match text_field::ask("Paste a layout:") {
+            Some(text) => text,
+            None => return,
+        };
+        let text = text.trim();
+        let state = if text.starts_with('{') {
+            serde_json::from_str::<TableState>(text).map_err(|err| err.to_string())
+        } else {
+            text.parse::<Position>()
+                .map(|position| position.table)
+                .map_err(|err| err.to_string())
+        };
+        let state = match state {
+            Ok(state) => state,
+            Err(err) => {
+                self.message = format!("Import failed: {}", err);
+                return;
+            }
+        };
+        match state.build_checked_within(state.profile, REST_TOLERANCE) {
+            Ok(table) => {
+                *pool_table = table;
+                self.selected = None;
+                self.message = "Layout imported".to_string();
+            }
+            Err(err) => self.message = format!("Import rejected, {}", err),
+        }
+    }
+
+    pub fn draw(
+        &self,
+        window: &mut Window,
+        pool_table: &PoolTable,
+        camera: &Camera,
+        font: &mut Asset<Font>,
+    ) -> Result<()> {
+        if self.grid {
+            self.draw_grid(window, camera, grid_size(pool_table));
+        }
+        for spot in spots() {
+            window.draw(
+                &Circle::new(camera.to_screen(spot), SPOT_SIZE),
+                Col(Color::WHITE.with_alpha(0.8)),
+            );
+        }
+
+        let kind = ball_kinds()[self.kind];
+        let lines = [
+            format!(
+                "Editor - add: {:?} (Tab), grid: {} (G), spot (S), clear (C)",
+                kind,
+                if self.grid { "on" } else { "off" }
+            ),
+            "Export (E), import (I), play (Return)".to_string(),
+            self.message.clone(),
+        ];
+        let screen = window.screen_size();
+        let top = screen.y - MARGIN - LINE_HEIGHT * lines.len() as f32;
+        window.draw(
+            &Rectangle::new((0., top), (screen.x, LINE_HEIGHT * lines.len() as f32)),
+            Col(Color::BLACK.with_alpha(0.5)),
+        );
+        font.execute(|font| {
+            let style = FontStyle::new(FONT_SIZE, Color::WHITE);
+            for (idx, line) in lines.iter().enumerate().filter(|(_, line)| !line.is_empty()) {
+                let image = font.render(line, &style)?;
+                let area = Rectangle::new(
+                    (MARGIN, top + LINE_HEIGHT * idx as f32),
+                    image.area().size,
+                );
+                window.draw(&area, Img(&image));
+            }
+            Ok(())
+        })
+    }
+
+    fn draw_grid(&self, window: &mut Window, camera: &Camera, size: f32) {
+        let left = MARGIN_LEFT + BORDER;
+        let top = MARGIN_TOP + BORDER;
+        let width = WIDTH + 2. * BAND;
+        let height = HEIGHT + 2. * BAND;
+        let color = Col(Color::WHITE.with_alpha(0.15));
+        let line = camera.scale(GRID_LINE_WIDTH).max(GRID_LINE_WIDTH);
+
+        let mut x = 0.;
+        while x <= width {
+            let rect = camera.rect(Vector2::new(left + x, top), Vector2::new(0., height));
+            window.draw(&grow(rect, line), color);
+            x += size;
+        }
+        let mut y = 0.;
+        while y <= height {
+            let rect = camera.rect(Vector2::new(left, top + y), Vector2::new(width, 0.));
+            window.draw(&grow(rect, line), color);
+            y += size;
+        }
+    }
+}
+
+// give a width to a line of the grid
+fn grow(rect: Rectangle, width: f32) -> Rectangle {
+    let size = Vector::new(rect.size.x.max(width), rect.size.y.max(width));
+    Rectangle::new(rect.pos, size)
+}
+
+fn move_ball(pool_table: &mut PoolTable, ball: BodyHandle, pos: Vector2<f32>) {
+    if let Some(body) = pool_table.world.rigid_body_mut(ball) {
+        body.set_position(Isometry2::new(pos, 0.));
+    }
+}
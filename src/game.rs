@@ -0,0 +1,299 @@
+use super::shot::ShotSummary;
+use super::{BallKind, GameMode, PocketedBall};
+
+/// The balls a player has to pocket in eight ball.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Group {
+    Red,
+    Yellow,
+}
+
+impl Group {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Group::Red => "red",
+            Group::Yellow => "yellow",
+        }
+    }
+
+    pub fn other(&self) -> Group {
+        match self {
+            Group::Red => Group::Yellow,
+            Group::Yellow => Group::Red,
+        }
+    }
+
+    fn of(kind: BallKind) -> Option<Group> {
+        match kind {
+            BallKind::Red => Some(Group::Red),
+            BallKind::Yellow => Some(Group::Yellow),
+            _ => None,
+        }
+    }
+}
+
+/// What the rules do with a ball that left the table.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OffTableRule {
+    /// the ball goes back on the foot spot, a foul except in practice
+    Respot,
+    /// the ball stays off the table, the shot is a foul
+    Foul,
+    /// the shooter loses the game
+    LossOfGame,
+}
+
+/// The players and their turns.
+#[derive(Clone)]
+pub struct Game {
+    mode: GameMode,
+    pub current_player: usize,
+    // group of every player, once the first colored ball is pocketed
+    pub groups: Option<[Group; 2]>,
+    // pocketed balls, in order, with the player who pocketed them
+    pocketed: Vec<(usize, PocketedBall)>,
+
+    foul: bool,
+    winner: Option<usize>,
+}
+
+impl Game {
+    pub fn new(mode: GameMode) -> Self {
+        Game {
+            mode,
+            current_player: 0,
+            groups: None,
+            pocketed: Vec::new(),
+            foul: false,
+            winner: None,
+        }
+    }
+
+    pub fn players(&self) -> usize {
+        match self.mode {
+            GameMode::Practice => 1,
+            GameMode::EightBall | GameMode::NineBall => 2,
+        }
+    }
+
+    pub fn ball_pocketed(&mut self, ball: &PocketedBall) {
+        if ball.kind == BallKind::White {
+            self.foul = true;
+            return;
+        }
+        self.pocketed.push((self.current_player, *ball));
+
+        if self.mode == GameMode::EightBall && self.groups.is_none() {
+            if let Some(group) = Group::of(ball.kind) {
+                let mut groups = [group.other(); 2];
+                groups[self.current_player] = group;
+                info!("Player {} plays the {} balls", self.current_player + 1, group.name());
+                self.groups = Some(groups);
+            }
+        }
+    }
+
+    /// A ball jumped off the table, or went through a cushion.
+    pub fn ball_off_table(&mut self, ball: &PocketedBall) -> OffTableRule {
+        let rule = match (self.mode, ball.kind) {
+            (_, BallKind::White) => OffTableRule::Foul,
+            (GameMode::Practice, _) => OffTableRule::Respot,
+            (GameMode::EightBall, BallKind::Black) => OffTableRule::LossOfGame,
+            (GameMode::EightBall, _) => OffTableRule::Respot,
+            (GameMode::NineBall, BallKind::Numbered(9)) => OffTableRule::Respot,
+            (GameMode::NineBall, _) => OffTableRule::Foul,
+        };
+        if self.mode != GameMode::Practice {
+            self.foul = true;
+        }
+        if rule == OffTableRule::LossOfGame {
+            let winner = (self.current_player + 1) % self.players();
+            info!("Player {} wins, the {:?} ball left the table", winner + 1, ball.kind);
+            self.winner = Some(winner);
+        }
+        rule
+    }
+
+    /// The player who won the game, if it is over.
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    pub fn group(&self, player: usize) -> Option<Group> {
+        self.groups.map(|groups| groups[player])
+    }
+
+    /// The player keeps the turn while pocketing balls without foul, a
+    /// miscue is a foul.
+    pub fn shot_complete(&mut self, summary: &ShotSummary) {
+        if summary.miscue && self.mode != GameMode::Practice {
+            self.foul = true;
+        }
+        let scored = summary
+            .pocketed
+            .iter()
+            .any(|ball| ball.kind != BallKind::White && ball.hole.is_some());
+        if !scored || self.foul {
+            self.current_player = (self.current_player + 1) % self.players();
+        }
+        self.foul = false;
+    }
+
+    /// Balls pocketed by a player, in order.
+    pub fn tray(&self, player: usize) -> Vec<PocketedBall> {
+        self.pocketed
+            .iter()
+            .filter(|(owner, _)| *owner == player)
+            .map(|(_, ball)| *ball)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ncollide2d::math::Vector as Vector2;
+    use nphysics2d::object::BodyHandle;
+
+    use super::*;
+    use crate::shot::Shot;
+    use crate::{simulation, PoolTable, MAX_FORCE};
+
+    fn off_table(kind: BallKind) -> PocketedBall {
+        PocketedBall {
+            ball: BodyHandle::ground(),
+            kind,
+            hole: None,
+            position: Vector2::new(0., 0.),
+        }
+    }
+
+    fn in_hole(kind: BallKind) -> PocketedBall {
+        PocketedBall {
+            hole: Some(0),
+            ..off_table(kind)
+        }
+    }
+
+    fn summary(pocketed: Vec<PocketedBall>) -> ShotSummary {
+        let shot = Shot {
+            angle: 0.,
+            force: 500.,
+            spin: Vector2::new(0., 0.),
+            elevation: 0.,
+        };
+        ShotSummary {
+            pocketed,
+            ..ShotSummary::new(shot)
+        }
+    }
+
+    #[test]
+    fn off_table_rules() {
+        let rules = [
+            (GameMode::Practice, BallKind::White, OffTableRule::Foul),
+            (GameMode::Practice, BallKind::Black, OffTableRule::Respot),
+            (GameMode::Practice, BallKind::Red, OffTableRule::Respot),
+            (GameMode::EightBall, BallKind::White, OffTableRule::Foul),
+            (GameMode::EightBall, BallKind::Black, OffTableRule::LossOfGame),
+            (GameMode::EightBall, BallKind::Yellow, OffTableRule::Respot),
+            (GameMode::NineBall, BallKind::White, OffTableRule::Foul),
+            (GameMode::NineBall, BallKind::Numbered(9), OffTableRule::Respot),
+            (GameMode::NineBall, BallKind::Numbered(3), OffTableRule::Foul),
+        ];
+        for &(mode, kind, rule) in rules.iter() {
+            let mut game = Game::new(mode);
+            assert_eq!(game.ball_off_table(&off_table(kind)), rule, "{:?} {:?}", mode, kind);
+            assert_eq!(game.foul, mode != GameMode::Practice, "{:?} {:?}", mode, kind);
+        }
+    }
+
+    #[test]
+    fn black_off_the_table_loses_the_game() {
+        let mut game = Game::new(GameMode::EightBall);
+        game.current_player = 1;
+        game.ball_off_table(&off_table(BallKind::Black));
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(Game::new(GameMode::EightBall).winner(), None);
+    }
+
+    #[test]
+    fn other_balls_off_the_table_do_not_end_the_game() {
+        let mut game = Game::new(GameMode::NineBall);
+        game.ball_off_table(&off_table(BallKind::Numbered(9)));
+        game.ball_off_table(&off_table(BallKind::Numbered(4)));
+        assert_eq!(game.winner(), None);
+    }
+
+    #[test]
+    fn a_foul_passes_the_turn_even_after_a_pocketed_ball() {
+        let mut game = Game::new(GameMode::NineBall);
+        let pocketed = in_hole(BallKind::Numbered(1));
+        game.ball_pocketed(&pocketed);
+        game.ball_off_table(&off_table(BallKind::Numbered(4)));
+        game.shot_complete(&summary(vec![pocketed]));
+        assert_eq!(game.current_player, 1);
+        assert!(!game.foul);
+    }
+
+    #[test]
+    fn a_pocketed_ball_keeps_the_turn() {
+        let mut game = Game::new(GameMode::EightBall);
+        let pocketed = in_hole(BallKind::Red);
+        game.ball_pocketed(&pocketed);
+        game.shot_complete(&summary(vec![pocketed]));
+        assert_eq!(game.current_player, 0);
+        assert_eq!(game.group(0), Some(Group::Red));
+        assert_eq!(game.group(1), Some(Group::Yellow));
+
+        game.shot_complete(&summary(Vec::new()));
+        assert_eq!(game.current_player, 1);
+    }
+
+    #[test]
+    fn a_miscue_is_a_foul() {
+        let mut game = Game::new(GameMode::EightBall);
+        let pocketed = in_hole(BallKind::Red);
+        game.ball_pocketed(&pocketed);
+        let mut miscue = summary(vec![pocketed]);
+        miscue.miscue = true;
+        game.shot_complete(&miscue);
+        assert_eq!(game.current_player, 1);
+
+        let mut practice = Game::new(GameMode::Practice);
+        practice.shot_complete(&miscue);
+        assert_eq!(practice.current_player, 0);
+    }
+
+    // the summary of a miscued shot, played on a table, fouls
+    #[test]
+    fn a_miscue_on_the_table_reaches_the_rules() {
+        let mut pool_table = PoolTable::new();
+        pool_table.initialze_world(GameMode::EightBall);
+        let shot = Shot {
+            angle: 0.,
+            force: MAX_FORCE,
+            spin: Vector2::new(1., 0.),
+            elevation: 0.,
+        };
+        simulation::play(&mut pool_table, &shot, |_, _, _| {});
+        let summary = pool_table.take_shot_complete().unwrap();
+        assert!(summary.miscue);
+        assert_eq!(summary.shot, shot);
+
+        let mut game = Game::new(GameMode::EightBall);
+        game.shot_complete(&summary);
+        assert_eq!(game.current_player, 1);
+    }
+
+    #[test]
+    fn practice_respots_without_a_foul() {
+        let mut game = Game::new(GameMode::Practice);
+        assert_eq!(
+            game.ball_off_table(&off_table(BallKind::Yellow)),
+            OffTableRule::Respot
+        );
+        game.shot_complete(&summary(Vec::new()));
+        assert_eq!(game.current_player, 0);
+        assert_eq!(game.winner(), None);
+    }
+}
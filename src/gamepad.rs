@@ -0,0 +1,185 @@
+use quicksilver::{
+    input::{ButtonState, Gamepad, GamepadAxis, GamepadButton},
+    lifecycle::Window,
+};
+
+// sticks never rest exactly at zero
+const DEAD_ZONE: f32 = 0.15;
+
+/// State of the gamepads, all the connected gamepads are merged.
+///
+/// The left stick rotates the cue quickly, the right stick rotates it
+/// precisely, the triggers change the power as far as they are pressed, A
+/// shoots and Start opens the menu.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct GamepadState {
+    // between -1 and 1
+    pub coarse_rotation: f32,
+    pub fine_rotation: f32,
+    // between -1 (left trigger) and 1 (right trigger)
+    pub power: f32,
+    pub shoot: bool,
+    pub menu: bool,
+
+    // menu navigation
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub activate: bool,
+}
+
+impl GamepadState {
+    pub fn read(window: &Window) -> Self {
+        window
+            .gamepads()
+            .iter()
+            .map(|gamepad| {
+                GamepadState::of(
+                    &|axis| gamepad[axis],
+                    &|button| gamepad[button],
+                    triggers(gamepad),
+                )
+            })
+            .fold(GamepadState::default(), GamepadState::merge)
+    }
+
+    /// The state of a gamepad, from its axes, its buttons and how far its
+    /// left and right triggers are pressed, between 0 and 1.
+    pub fn of(
+        axis: &dyn Fn(GamepadAxis) -> f32,
+        button: &dyn Fn(GamepadButton) -> ButtonState,
+        triggers: [f32; 2],
+    ) -> Self {
+        let pressed = |b| button(b) == ButtonState::Pressed;
+        let [left, right] = triggers;
+        GamepadState {
+            coarse_rotation: dead_zone(axis(GamepadAxis::LeftStickX)),
+            fine_rotation: dead_zone(axis(GamepadAxis::RightStickX)),
+            power: dead_zone(right) - dead_zone(left),
+            shoot: pressed(GamepadButton::FaceDown),
+            menu: pressed(GamepadButton::Start),
+            up: pressed(GamepadButton::DpadUp),
+            down: pressed(GamepadButton::DpadDown),
+            left: pressed(GamepadButton::DpadLeft),
+            right: pressed(GamepadButton::DpadRight),
+            // released, as the Return key of the menu.
+            activate: button(GamepadButton::FaceDown) == ButtonState::Released,
+        }
+    }
+
+    /// Both gamepads at once, the axes add up to their limits.
+    pub fn merge(self, other: GamepadState) -> Self {
+        let add = |a: f32, b: f32| (a + b).max(-1.).min(1.);
+        GamepadState {
+            coarse_rotation: add(self.coarse_rotation, other.coarse_rotation),
+            fine_rotation: add(self.fine_rotation, other.fine_rotation),
+            power: add(self.power, other.power),
+            shoot: self.shoot || other.shoot,
+            menu: self.menu || other.menu,
+            up: self.up || other.up,
+            down: self.down || other.down,
+            left: self.left || other.left,
+            right: self.right || other.right,
+            activate: self.activate || other.activate,
+        }
+    }
+}
+
+// the browser reports how far the triggers are pressed, in the buttons 6
+// and 7 of the standard mapping
+#[cfg(target_arch = "wasm32")]
+fn triggers(gamepad: &Gamepad) -> [f32; 2] {
+    use stdweb::web::Gamepad as WebGamepad;
+
+    let pad = WebGamepad::get_all()
+        .into_iter()
+        .flatten()
+        .find(|pad| pad.index() == gamepad.id());
+    match pad.map(|pad| pad.buttons()) {
+        Some(ref buttons) if buttons.len() > 7 => {
+            [buttons[6].value() as f32, buttons[7].value() as f32]
+        }
+        _ => digital_triggers(gamepad),
+    }
+}
+
+// the native backend only tells whether the triggers are down
+#[cfg(not(target_arch = "wasm32"))]
+fn triggers(gamepad: &Gamepad) -> [f32; 2] {
+    digital_triggers(gamepad)
+}
+
+fn digital_triggers(gamepad: &Gamepad) -> [f32; 2] {
+    let value = |button| if gamepad[button].is_down() { 1. } else { 0. };
+    [
+        value(GamepadButton::TriggerLeft),
+        value(GamepadButton::TriggerRight),
+    ]
+}
+
+fn dead_zone(value: f32) -> f32 {
+    if value.abs() < DEAD_ZONE {
+        0.
+    } else {
+        (value - DEAD_ZONE * value.signum()) / (1. - DEAD_ZONE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(stick: f32, triggers: [f32; 2], pressed: GamepadButton) -> GamepadState {
+        GamepadState::of(
+            &|axis| match axis {
+                GamepadAxis::LeftStickX => stick,
+                _ => 0.,
+            },
+            &|button| {
+                if button == pressed {
+                    ButtonState::Pressed
+                } else {
+                    ButtonState::NotPressed
+                }
+            },
+            triggers,
+        )
+    }
+
+    #[test]
+    fn the_power_follows_the_triggers() {
+        assert_eq!(pad(0., [0., 1.], GamepadButton::Start).power, 1.);
+        assert_eq!(pad(0., [1., 0.], GamepadButton::Start).power, -1.);
+        let half = pad(0., [0., 0.5], GamepadButton::Start).power;
+        assert!(half > 0.3 && half < 0.5, "{}", half);
+        assert_eq!(pad(0., [1., 1.], GamepadButton::Start).power, 0.);
+    }
+
+    #[test]
+    fn sticks_and_triggers_at_rest_do_nothing() {
+        let state = pad(0.1, [0.1, 0.05], GamepadButton::Start);
+        assert_eq!(state.coarse_rotation, 0.);
+        assert_eq!(state.power, 0.);
+    }
+
+    #[test]
+    fn the_buttons_map_to_the_actions() {
+        let state = pad(0., [0., 0.], GamepadButton::FaceDown);
+        assert!(state.shoot && !state.menu && !state.activate);
+        let state = pad(0., [0., 0.], GamepadButton::DpadUp);
+        assert!(state.up && !state.down && !state.shoot);
+        assert!(pad(0., [0., 0.], GamepadButton::Start).menu);
+    }
+
+    #[test]
+    fn gamepads_add_up_to_the_limits() {
+        let one = pad(0.8, [0., 1.], GamepadButton::DpadLeft);
+        let other = pad(0.9, [0., 0.6], GamepadButton::FaceDown);
+        let state = one.merge(other);
+        assert_eq!(state.coarse_rotation, 1.);
+        assert_eq!(state.power, 1.);
+        assert!(state.left && state.shoot);
+        assert_eq!(GamepadState::default().merge(one), one);
+    }
+}
@@ -0,0 +1,93 @@
+//! Height of the balls that leave the cloth, on top of the 2D world.
+
+// 9.81 m/s², a ball is 57 mm wide
+const GRAVITY: f32 = 82_400.;
+// part of the vertical speed kept when a ball bounces on the slate
+const SLATE_RESTITUTION: f32 = 0.5;
+// under this vertical speed a ball stays on the cloth, it would not rise
+// higher than a few millimeters
+const MIN_HOP_SPEED: f32 = 1_500.;
+
+/// A ball in the air, from the jump of the cue ball until it lands for good.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hop {
+    // height of the bottom of the ball above the cloth
+    pub height: f32,
+    // vertical speed, positive upwards
+    pub speed: f32,
+}
+
+impl Hop {
+    /// A ball leaving the cloth at `speed`, none when it is too slow to rise.
+    pub fn new(speed: f32) -> Option<Self> {
+        if speed < MIN_HOP_SPEED {
+            return None;
+        }
+        Some(Hop { height: 0., speed })
+    }
+
+    /// Fly during `dt` seconds and bounce on the slate. Return false when
+    /// the ball is back on the cloth.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.speed -= GRAVITY * dt;
+        self.height += self.speed * dt;
+        if self.height <= 0. {
+            self.height = 0.;
+            self.speed = -self.speed * SLATE_RESTITUTION;
+            return self.speed >= MIN_HOP_SPEED;
+        }
+        true
+    }
+
+    /// Energy of the vertical move and of the height, the ball weighs 1.
+    /// It never rises during the flight, and the bounces lose some.
+    pub fn energy(&self) -> f32 {
+        self.speed * self.speed * 0.5 + GRAVITY * self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1. / 60.;
+
+    #[test]
+    fn a_slow_ball_stays_on_the_cloth() {
+        assert_eq!(Hop::new(MIN_HOP_SPEED * 0.5), None);
+    }
+
+    #[test]
+    fn the_energy_never_rises_during_the_flight() {
+        let mut hop = Hop::new(20_000.).unwrap();
+        let mut energy = hop.energy();
+        let mut bounces = 0;
+        while hop.update(DT) {
+            let next = hop.energy();
+            assert!(
+                next <= energy,
+                "the energy rose from {} to {}",
+                energy,
+                next
+            );
+            if hop.height == 0. {
+                bounces += 1;
+            }
+            energy = next;
+        }
+        assert!(bounces > 0);
+        assert_eq!(hop.height, 0.);
+    }
+
+    #[test]
+    fn the_height_turns_into_speed() {
+        let mut hop = Hop::new(10_000.).unwrap();
+        let start = hop.energy();
+        while hop.speed > 0. {
+            hop.update(DT);
+        }
+        assert!(hop.height > 0.);
+        // the integration loses some at every step
+        assert!(hop.energy() > start * 0.8 && hop.energy() <= start);
+    }
+}
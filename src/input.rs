@@ -0,0 +1,327 @@
+//! Actions of the player and the keys bound to them.
+
+use std::collections::HashMap;
+
+use quicksilver::{
+    input::{ButtonState, Key},
+    lifecycle::Window,
+    saving::{load, save},
+};
+
+const APP_NAME: &str = "wasm-pool";
+const BINDINGS_PROFILE: &str = "bindings";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    AimLeft,
+    AimRight,
+    PowerUp,
+    PowerDown,
+    Shoot,
+    Fine,
+    Coarse,
+    SpinUp,
+    SpinDown,
+    SpinLeft,
+    SpinRight,
+    RaiseCue,
+    LowerCue,
+    Undo,
+    Pause,
+}
+
+pub const ACTIONS: [Action; 15] = [
+    Action::AimLeft,
+    Action::AimRight,
+    Action::PowerUp,
+    Action::PowerDown,
+    Action::Shoot,
+    Action::Fine,
+    Action::Coarse,
+    Action::SpinUp,
+    Action::SpinDown,
+    Action::SpinLeft,
+    Action::SpinRight,
+    Action::RaiseCue,
+    Action::LowerCue,
+    Action::Undo,
+    Action::Pause,
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::AimLeft => "Aim left",
+            Action::AimRight => "Aim right",
+            Action::PowerUp => "Power up",
+            Action::PowerDown => "Power down",
+            Action::Shoot => "Shoot",
+            Action::Fine => "Fine aim",
+            Action::Coarse => "Coarse aim",
+            Action::SpinUp => "Follow",
+            Action::SpinDown => "Draw",
+            Action::SpinLeft => "Left spin",
+            Action::SpinRight => "Right spin",
+            Action::RaiseCue => "Raise cue",
+            Action::LowerCue => "Lower cue",
+            Action::Undo => "Undo",
+            Action::Pause => "Pause",
+        }
+    }
+
+    // stable name used in the saved bindings
+    fn id(&self) -> &'static str {
+        match self {
+            Action::AimLeft => "aim_left",
+            Action::AimRight => "aim_right",
+            Action::PowerUp => "power_up",
+            Action::PowerDown => "power_down",
+            Action::Shoot => "shoot",
+            Action::Fine => "fine",
+            Action::Coarse => "coarse",
+            Action::SpinUp => "spin_up",
+            Action::SpinDown => "spin_down",
+            Action::SpinLeft => "spin_left",
+            Action::SpinRight => "spin_right",
+            Action::RaiseCue => "raise_cue",
+            Action::LowerCue => "lower_cue",
+            Action::Undo => "undo",
+            Action::Pause => "pause",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Action> {
+        ACTIONS.iter().find(|action| action.id() == id).cloned()
+    }
+}
+
+/// The keys that can be bound, with their names.
+const KEYS: &[(Key, &str)] = &[
+    (Key::Left, "Left"),
+    (Key::Right, "Right"),
+    (Key::Up, "Up"),
+    (Key::Down, "Down"),
+    (Key::Return, "Return"),
+    (Key::Space, "Space"),
+    (Key::Escape, "Escape"),
+    (Key::Back, "Backspace"),
+    (Key::Tab, "Tab"),
+    (Key::Delete, "Delete"),
+    (Key::Insert, "Insert"),
+    (Key::Home, "Home"),
+    (Key::End, "End"),
+    (Key::PageUp, "PageUp"),
+    (Key::PageDown, "PageDown"),
+    (Key::LControl, "LCtrl"),
+    (Key::RControl, "RCtrl"),
+    (Key::LAlt, "LAlt"),
+    (Key::RAlt, "RAlt"),
+    (Key::LShift, "LShift"),
+    (Key::RShift, "RShift"),
+    (Key::A, "A"),
+    (Key::B, "B"),
+    (Key::C, "C"),
+    (Key::D, "D"),
+    (Key::E, "E"),
+    (Key::F, "F"),
+    (Key::G, "G"),
+    (Key::H, "H"),
+    (Key::I, "I"),
+    (Key::J, "J"),
+    (Key::K, "K"),
+    (Key::L, "L"),
+    (Key::M, "M"),
+    (Key::N, "N"),
+    (Key::O, "O"),
+    (Key::P, "P"),
+    (Key::Q, "Q"),
+    (Key::R, "R"),
+    (Key::S, "S"),
+    (Key::T, "T"),
+    (Key::U, "U"),
+    (Key::V, "V"),
+    (Key::W, "W"),
+    (Key::X, "X"),
+    (Key::Y, "Y"),
+    (Key::Z, "Z"),
+    (Key::Key0, "0"),
+    (Key::Key1, "1"),
+    (Key::Key2, "2"),
+    (Key::Key3, "3"),
+    (Key::Key4, "4"),
+    (Key::Key5, "5"),
+    (Key::Key6, "6"),
+    (Key::Key7, "7"),
+    (Key::Key8, "8"),
+    (Key::Key9, "9"),
+    (Key::F1, "F1"),
+    (Key::F2, "F2"),
+    (Key::F3, "F3"),
+    (Key::F4, "F4"),
+    (Key::F5, "F5"),
+    (Key::F6, "F6"),
+    (Key::F7, "F7"),
+    (Key::F8, "F8"),
+    (Key::F9, "F9"),
+    (Key::F10, "F10"),
+    (Key::F11, "F11"),
+    (Key::F12, "F12"),
+];
+
+pub fn key_name(key: Key) -> &'static str {
+    KEYS.iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, name)| *name)
+        .unwrap_or("?")
+}
+
+fn key_from_name(name: &str) -> Option<Key> {
+    KEYS.iter().find(|(_, n)| *n == name).map(|(key, _)| *key)
+}
+
+/// The first bindable key pressed during this frame.
+pub fn pressed_key(window: &Window) -> Option<Key> {
+    let keyboard = window.keyboard();
+    KEYS.iter()
+        .map(|(key, _)| *key)
+        .find(|key| keyboard[*key] == ButtonState::Pressed)
+}
+
+/// Keys bound to every action, an action may have many keys.
+pub struct Bindings {
+    keys: HashMap<Action, Vec<Key>>,
+}
+
+impl Bindings {
+    pub fn new() -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(Action::AimLeft, vec![Key::Left]);
+        keys.insert(Action::AimRight, vec![Key::Right]);
+        keys.insert(Action::PowerUp, vec![Key::Down]);
+        keys.insert(Action::PowerDown, vec![Key::Up]);
+        keys.insert(Action::Shoot, vec![Key::Return]);
+        keys.insert(Action::Fine, vec![Key::LAlt]);
+        keys.insert(Action::Coarse, vec![Key::LControl, Key::RControl]);
+        keys.insert(Action::SpinUp, vec![Key::W]);
+        keys.insert(Action::SpinDown, vec![Key::S]);
+        keys.insert(Action::SpinLeft, vec![Key::A]);
+        keys.insert(Action::SpinRight, vec![Key::D]);
+        keys.insert(Action::RaiseCue, vec![Key::R]);
+        keys.insert(Action::LowerCue, vec![Key::F]);
+        keys.insert(Action::Undo, vec![Key::Back]);
+        keys.insert(Action::Pause, vec![Key::Escape]);
+        Bindings { keys }
+    }
+
+    /// The bindings saved by the player, or the default ones.
+    pub fn load() -> Self {
+        let mut bindings = Bindings::new();
+        match load::<HashMap<String, Vec<String>>>(APP_NAME, BINDINGS_PROFILE) {
+            Ok(saved) => {
+                for (id, names) in saved.iter() {
+                    if let Some(action) = Action::from_id(id) {
+                        let keys = names.iter().filter_map(|name| key_from_name(name));
+                        bindings.keys.insert(action, keys.collect());
+                    }
+                }
+            }
+            Err(err) => info!("Using the default key bindings: {}", err),
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        let saved: HashMap<String, Vec<String>> = self
+            .keys
+            .iter()
+            .map(|(action, keys)| {
+                let names = keys.iter().map(|key| key_name(*key).to_string());
+                (action.id().to_string(), names.collect())
+            })
+            .collect();
+        if let Err(err) = save(APP_NAME, BINDINGS_PROFILE, &saved) {
+            warn!("Unable to save the key bindings: {}", err);
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys.get(&action).map(|keys| keys.as_slice()).unwrap_or(&[])
+    }
+
+    /// Bind a key to an action, in place of its previous keys.
+    /// The key is unbound from the other actions, an action left without a
+    /// key gets the previous keys of this one: the keys are swapped.
+    pub fn bind(&mut self, action: Action, key: Key) {
+        let previous = self.keys.insert(action, vec![key]).unwrap_or_default();
+        for (other, keys) in self.keys.iter_mut() {
+            if *other == action || !keys.contains(&key) {
+                continue;
+            }
+            keys.retain(|k| *k != key);
+            if keys.is_empty() {
+                *keys = previous.iter().cloned().filter(|k| *k != key).collect();
+            }
+        }
+    }
+
+    pub fn is_down(&self, window: &Window, action: Action) -> bool {
+        let keyboard = window.keyboard();
+        self.keys(action).iter().any(|key| keyboard[*key].is_down())
+    }
+
+    pub fn is_pressed(&self, window: &Window, action: Action) -> bool {
+        let keyboard = window.keyboard();
+        self.keys(action)
+            .iter()
+            .any(|key| keyboard[*key] == ButtonState::Pressed)
+    }
+
+    /// Names of the keys bound to an action, as displayed in the menu.
+    pub fn label(&self, action: Action) -> String {
+        let keys = self.keys(action);
+        if keys.is_empty() {
+            return "-".to_string();
+        }
+        keys.iter()
+            .map(|key| key_name(*key))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_bound_key_leaves_the_other_actions() {
+        let mut bindings = Bindings::new();
+        bindings.bind(Action::Fine, Key::LControl);
+        assert_eq!(bindings.keys(Action::Fine), &[Key::LControl]);
+        assert_eq!(bindings.keys(Action::Coarse), &[Key::RControl]);
+    }
+
+    #[test]
+    fn the_last_key_of_an_action_is_swapped() {
+        let mut bindings = Bindings::new();
+        bindings.bind(Action::Shoot, Key::Escape);
+        assert_eq!(bindings.keys(Action::Shoot), &[Key::Escape]);
+        assert_eq!(bindings.keys(Action::Pause), &[Key::Return]);
+
+        bindings.bind(Action::Undo, Key::Return);
+        assert_eq!(bindings.keys(Action::Undo), &[Key::Return]);
+        assert_eq!(bindings.keys(Action::Pause), &[Key::Back]);
+    }
+
+    #[test]
+    fn every_action_keeps_a_key() {
+        let mut bindings = Bindings::new();
+        for action in ACTIONS.iter() {
+            bindings.bind(*action, Key::Escape);
+            bindings.bind(Action::Pause, Key::Escape);
+        }
+        for action in ACTIONS.iter() {
+            assert!(!bindings.keys(*action).is_empty(), "{:?} has no key", action);
+        }
+    }
+}
@@ -0,0 +1,1380 @@
+#[macro_use]
+extern crate log;
+
+pub mod animation;
+mod ball_sprites;
+mod camera;
+mod debug;
+pub mod diagram;
+mod drill;
+mod editor;
+mod game;
+mod gamepad;
+mod hop;
+mod input;
+mod link;
+mod menu;
+pub mod miscue;
+pub mod notation;
+pub mod physics;
+mod rest;
+pub mod shot;
+pub mod simulation;
+mod sound;
+mod spin;
+mod sprite;
+pub mod state;
+mod stroke;
+mod text_field;
+mod throw;
+mod tray;
+mod ui;
+
+use std::collections::HashMap;
+use std::vec::Vec;
+
+use nalgebra as na;
+
+use ncollide2d::{
+    events::ContactEvent,
+    math::{Isometry as Isometry2, Vector as Vector2},
+    shape::{Ball, Cuboid, ShapeHandle},
+    world::{CollisionGroups, CollisionObjectHandle},
+};
+
+use nphysics2d::{
+    math::Velocity,
+    object::{BodyHandle, BodyStatus, Material},
+    solver::{SignoriniCoulombPyramidModel, SignoriniModel},
+    volumetric::Volumetric,
+    world::World,
+};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::hop::Hop;
+use crate::miscue::miscue;
+use crate::physics::PhysicsProfile;
+use crate::rest::RestDetector;
+use crate::shot::{Shot, ShotOutcome, ShotSummary};
+use crate::spin::{side_spin, side_spin_rebound, CueSpin};
+use crate::throw::throw;
+
+pub use crate::ui::run;
+
+const COLLIDER_MARGIN: f32 = 0.1;
+/// Radius of the balls of the standard set, the table sizes are in the same units.
+pub const BALL_SIZE: f32 = 240.0;
+pub const WIDTH: f32 = 15440.0;
+pub const HEIGHT: f32 = 8000.;
+const MARGIN_TOP: f32 = 3000.;
+const MARGIN_LEFT: f32 = 2000.;
+pub const BORDER: f32 = 560.;
+pub const BAND: f32 = 160.;
+pub const HOLE_SIZE: f32 = 320.;
+
+pub const MAX_FORCE: f32 = 1400.;
+
+const TIME_STEP: f32 = 1. / 60.;
+// a third of the radius of the balls, and half the width of the bands
+const MAX_SUB_STEP_TRAVEL: f32 = 80.;
+const MAX_SUB_STEPS: usize = 32;
+
+// collision groups of the bodies, a ball in the air skips some of them
+const BALL_GROUP: usize = 0;
+const HOLE_GROUP: usize = 1;
+const CUSHION_GROUP: usize = 2;
+// heights, relative to the radius, above which a ball in the air passes
+// over the other balls and over the rails
+const BALL_CLEARANCE: f32 = 1.;
+const CUSHION_CLEARANCE: f32 = 1.5;
+
+pub struct ZGravity {
+    // part of the speed lost every second, see PhysicsProfile::cloth_drag
+    drag: f32,
+}
+
+impl ZGravity {
+    // Creates a new radial force generator.
+    pub fn new(drag: f32) -> Self {
+        ZGravity { drag }
+    }
+
+    pub fn apply_force(&mut self, world: &mut World<f32>, body: BodyHandle) {
+        let mut part = world.body_part_mut(body);
+        let mut vel = part.as_ref().velocity();
+
+        vel.linear.x = -vel.linear.x * self.drag;
+        vel.linear.y = -vel.linear.y * self.drag;
+
+        let inertia = part.as_ref().inertia();
+        let force = inertia * vel;
+        part.apply_force(&force);
+    }
+}
+
+fn collision_groups(group: usize) -> CollisionGroups {
+    CollisionGroups::new().with_membership(&[group])
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    EightBall,
+    NineBall,
+    Practice,
+}
+
+impl GameMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::EightBall => "8-ball",
+            GameMode::NineBall => "9-ball",
+            GameMode::Practice => "practice",
+        }
+    }
+
+    pub fn next(&self) -> GameMode {
+        match self {
+            GameMode::EightBall => GameMode::NineBall,
+            GameMode::NineBall => GameMode::Practice,
+            GameMode::Practice => GameMode::EightBall,
+        }
+    }
+
+    pub fn previous(&self) -> GameMode {
+        match self {
+            GameMode::EightBall => GameMode::Practice,
+            GameMode::NineBall => GameMode::EightBall,
+            GameMode::Practice => GameMode::NineBall,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BallKind {
+    White,
+    Black,
+    Red,
+    Yellow,
+    Numbered(u8),
+}
+
+/// A ball that left the table, in a hole or over the border.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PocketedBall {
+    pub ball: BodyHandle,
+    pub kind: BallKind,
+    // index in the holes, None if the ball jumped off the table
+    pub hole: Option<usize>,
+    // last position on the table
+    pub position: Vector2<f32>,
+}
+
+/// What prevents a ball from being placed on the table.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Overlap {
+    OffTable,
+    Cushion,
+    Hole,
+    Ball,
+    // a second cue ball or black ball
+    Duplicate,
+}
+
+impl Overlap {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Overlap::OffTable => "off the table",
+            Overlap::Cushion => "in a cushion",
+            Overlap::Hole => "in a hole",
+            Overlap::Ball => "on another ball",
+            Overlap::Duplicate => "already on the table",
+        }
+    }
+}
+
+/// Something that happened on the table during a step.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TableEvent {
+    CueStrike {
+        speed: f32,
+    },
+    BallHit {
+        ball1: BodyHandle,
+        ball2: BodyHandle,
+        speed: f32,
+    },
+    CushionHit {
+        ball: BodyHandle,
+        speed: f32,
+    },
+    Pocketed {
+        ball: BodyHandle,
+        hole: BodyHandle,
+        speed: f32,
+    },
+    // the ball jumped or went through a cushion
+    OffTable {
+        ball: BodyHandle,
+        speed: f32,
+    },
+}
+
+/// The balls and the table, in the physics world.
+pub struct PoolTable {
+    world: World<f32>,
+    z_gravity: ZGravity,
+    mode: GameMode,
+    profile: PhysicsProfile,
+
+    holes: Vec<BodyHandle>,
+    bounds: Vec<(BodyHandle, CollisionObjectHandle)>,
+
+    white_ball_handle: Option<BodyHandle>,
+    white_ball_handle_dropped: Option<BodyHandle>,
+    ball_8_handle: Option<BodyHandle>,
+
+    dropped_balls_handles: Vec<BodyHandle>,
+    // every ball that left the table, in order
+    pocketed_balls: Vec<PocketedBall>,
+
+    yellow_balls_handles: Vec<BodyHandle>,
+    red_balls_handles: Vec<BodyHandle>,
+    // balls of the 9-ball game, with their number
+    numbered_balls_handles: Vec<(u8, BodyHandle)>,
+
+    events: Vec<TableEvent>,
+    // spin of the last shot, until its effects are applied
+    cue_spin: Option<CueSpin>,
+    // side spin given by the throw to the object balls, until their next cushion
+    ball_spins: HashMap<BodyHandle, f32>,
+    // balls in the air, after a jump shot
+    hops: HashMap<BodyHandle, Hop>,
+    rest: RestDetector,
+    // the shot in progress, and the last one once complete
+    shot: Option<ShotSummary>,
+    shot_complete: Option<ShotSummary>,
+}
+
+impl PoolTable {
+    pub fn new() -> PoolTable {
+        PoolTable::with_profile(PhysicsProfile::standard())
+    }
+
+    pub fn with_profile(profile: PhysicsProfile) -> PoolTable {
+        let mut world: World<f32> = World::new();
+        let param = world.integration_parameters_mut();
+        param.dt = TIME_STEP;
+
+        // the friction of the cushions needs the slower Coulomb model
+        if profile.cushion_friction > 0. {
+            let model: SignoriniCoulombPyramidModel<f32> = SignoriniCoulombPyramidModel::new();
+            world.set_contact_model(model);
+        } else {
+            let model: SignoriniModel<f32> = SignoriniModel::new();
+            world.set_contact_model(model);
+        }
+
+        let z_gravity = ZGravity::new(profile.cloth_drag);
+
+        PoolTable {
+            world,
+            z_gravity,
+            mode: GameMode::EightBall,
+            profile,
+            holes: Vec::with_capacity(6),
+            bounds: Vec::new(),
+            white_ball_handle: None,
+            white_ball_handle_dropped: None,
+            ball_8_handle: None,
+            dropped_balls_handles: Vec::new(),
+            pocketed_balls: Vec::new(),
+
+            yellow_balls_handles: Vec::new(),
+            red_balls_handles: Vec::new(),
+            numbered_balls_handles: Vec::new(),
+            events: Vec::new(),
+            cue_spin: None,
+            ball_spins: HashMap::new(),
+            hops: HashMap::new(),
+            rest: RestDetector::new(),
+            shot: None,
+            shot_complete: None,
+        }
+    }
+    fn initialize_bounds(&mut self) {
+        let vertical_height = HEIGHT + 2. * BAND;
+        let vertical_height_thin = HEIGHT - HOLE_SIZE - 3. * BAND;
+        let horizontal_width = WIDTH + 2. * BAND;
+        let horizontal_width_thin = horizontal_width - HOLE_SIZE - 2. * BAND;
+        let half_horizontal_width_thin = (horizontal_width - 3. * HOLE_SIZE - 7. * BAND) / 2.;
+
+        let top = MARGIN_TOP + BORDER;
+        let left = MARGIN_LEFT + BORDER;
+
+        let half_band = BAND / 2.;
+        let half_vertical_height = vertical_height / 2.;
+        let half_horizontal_width = horizontal_width / 2.;
+        let half_half_horizontal_width_thin = half_horizontal_width_thin / 2.;
+        let half_vertical_height_thin = vertical_height_thin / 2.;
+
+        // top
+        self.add_bound(
+            left + half_horizontal_width,
+            top - BAND,
+            half_horizontal_width,
+            BAND,
+        );
+        self.add_bound(
+            left + HOLE_SIZE + BAND * 1.5 + half_half_horizontal_width_thin,
+            top + half_band,
+            half_half_horizontal_width_thin,
+            half_band,
+        );
+
+        self.add_bound(
+            left + 2. * HOLE_SIZE
+                + 5.5 * BAND
+                + half_horizontal_width_thin
+                + half_half_horizontal_width_thin,
+            top + half_band,
+            half_half_horizontal_width_thin,
+            half_band,
+        );
+
+        // left
+        self.add_bound(
+            left - BAND,
+            top + half_vertical_height,
+            BAND,
+            half_vertical_height,
+        );
+        self.add_bound(
+            left + half_band,
+            top + HOLE_SIZE + BAND * 1.5 + half_vertical_height_thin,
+            half_band,
+            half_vertical_height_thin,
+        );
+
+        // bottom
+        self.add_bound(
+            left + half_horizontal_width,
+            top + vertical_height + BAND,
+            half_horizontal_width,
+            BAND,
+        );
+
+        self.add_bound(
+            left + HOLE_SIZE + BAND * 1.5 + half_half_horizontal_width_thin,
+            top + vertical_height_thin + 2. * HOLE_SIZE + 2.5 * BAND,
+            half_half_horizontal_width_thin,
+            half_band,
+        );
+
+        self.add_bound(
+            left + 2. * HOLE_SIZE
+                + 5.5 * BAND
+                + half_horizontal_width_thin
+                + half_half_horizontal_width_thin,
+            top + vertical_height_thin + 2. * HOLE_SIZE + 2.5 * BAND,
+            half_half_horizontal_width_thin,
+            half_band,
+        );
+
+        // right
+        self.add_bound(
+            left + horizontal_width + BAND,
+            top + half_vertical_height,
+            BAND,
+            half_vertical_height,
+        );
+
+        self.add_bound(
+            left + horizontal_width_thin + HOLE_SIZE + BAND * 1.5,
+            top + HOLE_SIZE + BAND * 1.5 + half_vertical_height_thin,
+            half_band,
+            half_vertical_height_thin,
+        );
+    }
+
+    fn initialize_balls(&mut self) {
+        let center_y = MARGIN_TOP + BORDER + HEIGHT * 0.5;
+        let white_ball_handle = self.add_cue_ball(MARGIN_LEFT + BORDER + WIDTH * 0.25, center_y);
+
+        let center_x = MARGIN_LEFT + BORDER + WIDTH * 0.75;
+        let radius = self.profile.ball_radius;
+
+        //     r
+        let ball_r1 = self.add_ball(center_x - 4. * radius, center_y);
+
+        //    y r  ( right to left )
+
+        let ball_r2 = self.add_ball(center_x - 2. * radius, center_y - 1. * radius);
+
+        let ball_y1 = self.add_ball(center_x - 2. * radius, center_y + 1. * radius);
+
+        //   r b y  ( right to left )
+
+        let ball_y2 = self.add_ball(center_x, center_y - 2. * radius);
+
+        let ball_8_handle = self.add_ball(center_x, center_y);
+
+        let ball_r3 = self.add_ball(center_x, center_y + 2. * radius);
+
+        //  y r y r  ( right to left )
+        let ball_r4 = self.add_ball(center_x + 2. * radius, center_y - 3. * radius);
+        let ball_y3 = self.add_ball(center_x + 2. * radius, center_y - 1. * radius);
+        let ball_r5 = self.add_ball(center_x + 2. * radius, center_y + 1. * radius);
+        let ball_y4 = self.add_ball(center_x + 2. * radius, center_y + 3. * radius);
+
+        // r y r y y ( right to left )
+        let ball_y5 = self.add_ball(center_x + 4. * radius, center_y - 4. * radius);
+        let ball_y6 = self.add_ball(center_x + 4. * radius, center_y - 2. * radius);
+        let ball_r6 = self.add_ball(center_x + 4. * radius, center_y);
+        let ball_y7 = self.add_ball(center_x + 4. * radius, center_y + 2. * radius);
+        let ball_r7 = self.add_ball(center_x + 4. * radius, center_y + 4. * radius);
+
+        self.red_balls_handles = vec![
+            ball_r1, ball_r2, ball_r3, ball_r4, ball_r5, ball_r6, ball_r7,
+        ];
+        self.yellow_balls_handles = vec![
+            ball_y1, ball_y2, ball_y3, ball_y4, ball_y5, ball_y6, ball_y7,
+        ];
+
+        self.ball_8_handle = Some(ball_8_handle);
+        self.white_ball_handle = Some(white_ball_handle);
+    }
+
+    fn initialize_nine_balls(&mut self) {
+        let center_y = MARGIN_TOP + BORDER + HEIGHT * 0.5;
+        let white_ball_handle = self.add_cue_ball(MARGIN_LEFT + BORDER + WIDTH * 0.25, center_y);
+
+        let center_x = MARGIN_LEFT + BORDER + WIDTH * 0.75;
+        let radius = self.profile.ball_radius;
+
+        // diamond rack, the 1 on the apex and the 9 in the middle
+        let rack = [
+            (1, -4., 0.),
+            (2, -2., -1.),
+            (3, -2., 1.),
+            (4, 0., -2.),
+            (9, 0., 0.),
+            (5, 0., 2.),
+            (6, 2., -1.),
+            (7, 2., 1.),
+            (8, 4., 0.),
+        ];
+        for (number, x, y) in rack.iter() {
+            let ball = self.add_ball(center_x + x * radius, center_y + y * radius);
+            self.numbered_balls_handles.push((*number, ball));
+        }
+
+        self.white_ball_handle = Some(white_ball_handle);
+    }
+
+    fn initialize_practice_balls(&mut self) {
+        // the cue ball and the black on its spot, to train long shots.
+        let center_y = MARGIN_TOP + BORDER + HEIGHT * 0.5;
+        let white_ball_handle = self.add_cue_ball(MARGIN_LEFT + BORDER + WIDTH * 0.25, center_y);
+        let ball_8_handle = self.add_ball(MARGIN_LEFT + BORDER + WIDTH * 0.75, center_y);
+
+        self.ball_8_handle = Some(ball_8_handle);
+        self.white_ball_handle = Some(white_ball_handle);
+    }
+
+    fn initialize_holes(&mut self) {
+        // add hole sensors
+        //
+
+        // top left
+        self.add_hole(
+            MARGIN_LEFT + BORDER + BAND * 0.5,
+            MARGIN_TOP + BORDER + BAND * 0.5,
+        );
+        // top
+        self.add_hole(
+            MARGIN_LEFT + BORDER + BAND + WIDTH * 0.5,
+            MARGIN_TOP + BORDER + BAND * 0.5,
+        );
+
+        // top right
+        self.add_hole(
+            MARGIN_LEFT + BORDER + BAND + WIDTH + BAND * 0.5,
+            MARGIN_TOP + BORDER + BAND * 0.5,
+        );
+
+        // bottom right hole
+        self.add_hole(
+            MARGIN_LEFT + BORDER + BAND + WIDTH + BAND * 0.25,
+            MARGIN_TOP + BORDER + HEIGHT + BAND * 1.25,
+        );
+        // bottom hole
+        self.add_hole(
+            MARGIN_LEFT + BORDER + BAND + WIDTH * 0.5,
+            MARGIN_TOP + BORDER + HEIGHT + BAND * 1.5,
+        );
+
+        // bottom left hole
+        self.add_hole(
+            MARGIN_LEFT + BORDER + BAND * 0.5,
+            MARGIN_TOP + BORDER + HEIGHT + BAND * 1.5,
+        );
+    }
+
+    pub fn initialze_world(&mut self, mode: GameMode) {
+        self.mode = mode;
+        self.initialize_holes();
+        self.initialize_bounds();
+        match mode {
+            GameMode::EightBall => self.initialize_balls(),
+            GameMode::NineBall => self.initialize_nine_balls(),
+            GameMode::Practice => self.initialize_practice_balls(),
+        }
+
+        //let mut z_gravity: ZGravity = ZGravity::new(Vec::new());
+        //self.world.add_force_generator(z_gravity);
+    }
+
+    /// Add a ball of the given kind, used to restore a position.
+    fn place_ball(&mut self, kind: BallKind, x: f32, y: f32) -> BodyHandle {
+        let ball = if kind == BallKind::White {
+            self.add_cue_ball(x, y)
+        } else {
+            self.add_ball(x, y)
+        };
+        // the world reuses the handles of the removed bodies
+        self.dropped_balls_handles.retain(|b| *b != ball);
+        match kind {
+            BallKind::White => self.white_ball_handle = Some(ball),
+            BallKind::Black => self.ball_8_handle = Some(ball),
+            BallKind::Red => self.red_balls_handles.push(ball),
+            BallKind::Yellow => self.yellow_balls_handles.push(ball),
+            BallKind::Numbered(number) => self.numbered_balls_handles.push((number, ball)),
+        }
+        ball
+    }
+
+    /// Take a ball off the table, without pocketing it.
+    fn remove_ball(&mut self, ball: BodyHandle) {
+        if self.white_ball_handle == Some(ball) {
+            self.white_ball_handle = None;
+        }
+        if self.ball_8_handle == Some(ball) {
+            self.ball_8_handle = None;
+        }
+        self.red_balls_handles.retain(|b| *b != ball);
+        self.yellow_balls_handles.retain(|b| *b != ball);
+        self.numbered_balls_handles.retain(|(_, b)| *b != ball);
+        self.hops.remove(&ball);
+        self.world.remove_bodies(&[ball]);
+    }
+
+    /// What a ball placed at a position would overlap, the ignored ball
+    /// excepted.
+    fn overlap(&self, pos: Vector2<f32>, ignore: Option<BodyHandle>) -> Option<Overlap> {
+        self.overlap_within(pos, ignore, 0.)
+    }
+
+    /// Like overlap, but the ball may sink `tolerance` in the cushions and
+    /// in the other balls.
+    fn overlap_within(
+        &self,
+        pos: Vector2<f32>,
+        ignore: Option<BodyHandle>,
+        tolerance: f32,
+    ) -> Option<Overlap> {
+        let (x, y) = state::table_position(pos);
+        if x < 0. || y < 0. || x > WIDTH + 2. * BAND || y > HEIGHT + 2. * BAND {
+            return Some(Overlap::OffTable);
+        }
+
+        // the ball must not touch the hole sensor
+        let in_hole = self.holes.iter().any(|hole| {
+            let center = self.world.body_part(*hole).position().translation.vector;
+            (center - pos).norm() < HOLE_SIZE + self.profile.ball_radius * 0.5
+        });
+        if in_hole {
+            return Some(Overlap::Hole);
+        }
+
+        let in_cushion = self.bounds.iter().any(|(_, handle)| {
+            let collider = self.world.collider(*handle).unwrap();
+            match collider.shape().as_shape::<Cuboid<f32>>() {
+                Some(cuboid) => {
+                    let offset = collider.position().translation.vector - pos;
+                    let radius = self.profile.ball_radius - tolerance;
+                    let half_size = cuboid.half_extents() + Vector2::repeat(radius);
+                    offset.x.abs() < half_size.x && offset.y.abs() < half_size.y
+                }
+                None => false,
+            }
+        });
+        if in_cushion {
+            return Some(Overlap::Cushion);
+        }
+
+        let on_ball = self
+            .balls()
+            .into_iter()
+            .filter(|ball| Some(*ball) != ignore)
+            .any(|ball| {
+                let center = self.world.body_part(ball).position().translation.vector;
+                (center - pos).norm() < self.profile.ball_radius * 2. - tolerance
+            });
+        if on_ball {
+            return Some(Overlap::Ball);
+        }
+        None
+    }
+
+    fn add_ball(&mut self, x: f32, y: f32) -> BodyHandle {
+        let density = self.profile.ball_density(false);
+        self.add_ball_body(x, y, density)
+    }
+
+    fn add_cue_ball(&mut self, x: f32, y: f32) -> BodyHandle {
+        let density = self.profile.ball_density(true);
+        self.add_ball_body(x, y, density)
+    }
+
+    fn add_ball_body(&mut self, x: f32, y: f32, density: f32) -> BodyHandle {
+        let ball_shape = self.ball_shape();
+        let ball_material = self.ball_material();
+        let ball_pos = Isometry2::new(Vector2::new(x, y), na::zero());
+
+        let inertia = ball_shape.inertia(density);
+        let center_of_mass = ball_shape.center_of_mass();
+        let ball_handle = self.world.add_rigid_body(ball_pos, inertia, center_of_mass);
+        // z_gravity.add_body_part(ball_handle);
+
+        let collider = self.world.add_collider(
+            COLLIDER_MARGIN,
+            ball_shape.clone(),
+            ball_handle,
+            Isometry2::identity(),
+            ball_material.clone(),
+        );
+        self.set_collision_groups(collider, collision_groups(BALL_GROUP));
+        ball_handle
+    }
+
+    pub fn add_hole(&mut self, x: f32, y: f32) {
+        // the hole size does not collide on the displayed border, the ball must enter in it.
+        // we fake the display right now.
+        let hole_shape: ShapeHandle<f32> =
+            ShapeHandle::new(Ball::new(HOLE_SIZE - self.profile.ball_radius * 0.5));
+        let inertia = hole_shape.inertia(1.0);
+        let center_of_mass = hole_shape.center_of_mass();
+
+        let pos = Vector2::new(x, y);
+        let pos = Isometry2::new(pos, na::zero());
+        let hole = self.world.add_rigid_body(pos, inertia, center_of_mass);
+        self.world
+            .rigid_body_mut(hole)
+            .unwrap()
+            .set_status(BodyStatus::Static);
+        let collider = self.world.add_collider(
+            COLLIDER_MARGIN,
+            hole_shape,
+            hole,
+            Isometry2::identity(),
+            Material::default(),
+        );
+        self.set_collision_groups(collider, collision_groups(HOLE_GROUP));
+        self.holes.push(hole);
+    }
+
+    pub fn add_bound(&mut self, center_x: f32, center_y: f32, width: f32, height: f32) {
+        let bound_shape: ShapeHandle<f32> =
+            ShapeHandle::new(Cuboid::new(Vector2::new(width, height)));
+        let inertia = bound_shape.inertia(1.0);
+        let center_of_mass = bound_shape.center_of_mass();
+
+        let pos = Isometry2::new(Vector2::new(center_x, center_y), na::zero());
+        let bound = self.world.add_rigid_body(pos, inertia, center_of_mass);
+        self.world
+            .rigid_body_mut(bound)
+            .unwrap()
+            .set_status(BodyStatus::Static);
+        let collider = self.world.add_collider(
+            COLLIDER_MARGIN,
+            bound_shape,
+            BodyHandle::ground(),
+            pos,
+            self.bound_material(),
+        );
+        self.set_collision_groups(collider, collision_groups(CUSHION_GROUP));
+        self.bounds.push((bound, collider));
+    }
+
+    fn set_collision_groups(&mut self, collider: CollisionObjectHandle, groups: CollisionGroups) {
+        self.world
+            .collision_world_mut()
+            .set_collision_groups(collider, groups);
+    }
+
+    fn ball_collider(&self, ball: BodyHandle) -> Option<CollisionObjectHandle> {
+        self.world
+            .colliders()
+            .find(|collider| collider.data().body() == ball)
+            .map(|collider| collider.handle())
+    }
+
+    fn ball_material(&self) -> Material<f32> {
+        Material::new(self.profile.ball_restitution, 0.)
+    }
+
+    fn ball_shape(&self) -> ShapeHandle<f32> {
+        ShapeHandle::new(Ball::new(self.profile.ball_radius))
+    }
+
+    fn bound_material(&self) -> Material<f32> {
+        Material::new(self.profile.cushion_restitution, self.profile.cushion_friction)
+    }
+
+    pub fn profile(&self) -> &PhysicsProfile {
+        &self.profile
+    }
+
+    fn drop_ball(&mut self, ball: &BodyHandle, hole: Option<BodyHandle>) {
+        if self.dropped_balls_handles.contains(ball) {
+            info!("!!! ball dropped");
+            return;
+        }
+        self.ball_spins.remove(ball);
+        self.hops.remove(ball);
+
+        if let Some(kind) = self.ball_kind(*ball) {
+            let position = self.world.body_part(*ball).position().translation.vector;
+            let pocketed = PocketedBall {
+                ball: *ball,
+                kind,
+                hole: hole.and_then(|hole| self.hole_index(hole)),
+                position,
+            };
+            self.pocketed_balls.push(pocketed);
+            if let Some(ref mut summary) = self.shot {
+                summary.pocketed.push(pocketed);
+            }
+        }
+
+        if Some(*ball) == self.white_ball_handle {
+            info!("!!! drop the white ball");
+            self.white_ball_handle_dropped = self.white_ball_handle;
+            self.white_ball_handle = None;
+        } else if Some(*ball) == self.ball_8_handle {
+            info!("!!! drop the 8 ball",);
+            self.ball_8_handle = None;
+        } else if self.red_balls_handles.contains(ball) {
+            info!("!!! drop a red ball",);
+            self.red_balls_handles = self
+                .red_balls_handles
+                .iter()
+                .filter(|b| b != &ball)
+                .map(|b| b.clone())
+                .collect();
+        } else if self.yellow_balls_handles.contains(ball) {
+            info!("!!! drop a yellow ball");
+            self.yellow_balls_handles = self
+                .yellow_balls_handles
+                .iter()
+                .filter(|b| b != &ball)
+                .map(|b| b.clone())
+                .collect();
+        } else if let Some(idx) = self
+            .numbered_balls_handles
+            .iter()
+            .position(|(_, b)| b == ball)
+        {
+            let (number, _) = self.numbered_balls_handles.remove(idx);
+            info!("!!! drop the {} ball", number);
+        }
+        self.dropped_balls_handles.push(ball.clone());
+        self.world.remove_bodies(&[ball.clone()]);
+    }
+
+    // Return the ball and the hole it fell in.
+    fn handle_contact_event(&self, event: &ContactEvent) -> Option<(BodyHandle, BodyHandle)> {
+        if let &ContactEvent::Started(collider1, collider2) = event {
+            let body1 = self.world.collider(collider1).unwrap().data().body();
+            let body2 = self.world.collider(collider2).unwrap().data().body();
+
+            if self.holes.contains(&body1) {
+                return Some((body2, body1));
+            }
+            if self.holes.contains(&body2) {
+                return Some((body1, body2));
+            }
+        }
+        None
+    }
+
+    // Impact of a contact that started, the speed is the relative velocity
+    // of the bodies before the step.
+    fn impact_event(
+        &self,
+        event: &ContactEvent,
+        velocities: &HashMap<BodyHandle, Vector2<f32>>,
+    ) -> Option<TableEvent> {
+        if let &ContactEvent::Started(collider1, collider2) = event {
+            let body1 = self.world.collider(collider1).unwrap().data().body();
+            let body2 = self.world.collider(collider2).unwrap().data().body();
+            let velocity = |body| velocities.get(&body).cloned().unwrap_or(na::zero());
+            let speed = (velocity(body1) - velocity(body2)).norm();
+
+            if self.holes.contains(&body1) {
+                return Some(TableEvent::Pocketed { ball: body2, hole: body1, speed });
+            }
+            if self.holes.contains(&body2) {
+                return Some(TableEvent::Pocketed { ball: body1, hole: body2, speed });
+            }
+            if self.bounds.iter().any(|(_, collider)| *collider == collider1) {
+                return Some(TableEvent::CushionHit { ball: body2, speed });
+            }
+            if self.bounds.iter().any(|(_, collider)| *collider == collider2) {
+                return Some(TableEvent::CushionHit { ball: body1, speed });
+            }
+            return Some(TableEvent::BallHit {
+                ball1: body1,
+                ball2: body2,
+                speed,
+            });
+        }
+        None
+    }
+
+    /// The last record of a ball that left the table, handles are reused.
+    pub fn pocketed_ball(&self, ball: BodyHandle) -> Option<PocketedBall> {
+        self.pocketed_balls
+            .iter()
+            .rev()
+            .find(|pocketed| pocketed.ball == ball)
+            .cloned()
+    }
+
+    pub fn white_ball(&self) -> Option<BodyHandle> {
+        self.white_ball_handle
+    }
+
+    /// Every ball that left the table, in order.
+    pub fn pocketed_balls(&self) -> &[PocketedBall] {
+        &self.pocketed_balls
+    }
+
+    /// The balls taken off the table by a hole or over a rail.
+    pub fn dropped_balls(&self) -> &[BodyHandle] {
+        &self.dropped_balls_handles
+    }
+
+    /// Position of a ball relative to the table, as in `TableState`, none
+    /// when it is not on the table.
+    pub fn ball_position(&self, ball: BodyHandle) -> Option<(f32, f32)> {
+        if !self.balls().contains(&ball) {
+            return None;
+        }
+        let pos = self.world.body_part(ball).position().translation.vector;
+        Some(state::table_position(pos))
+    }
+
+    /// Index of a hole, in the order they are added.
+    pub fn hole_index(&self, hole: BodyHandle) -> Option<usize> {
+        self.holes.iter().position(|h| *h == hole)
+    }
+
+    /// Events of the steps since the last call.
+    pub fn take_events(&mut self) -> Vec<TableEvent> {
+        std::mem::replace(&mut self.events, Vec::new())
+    }
+
+    fn respawn_white_ball(&mut self) {
+        let x = MARGIN_LEFT + BORDER + WIDTH * 0.25;
+        let y = MARGIN_TOP + BORDER + HEIGHT * 0.5;
+
+        self.place_ball(BallKind::White, x, y);
+        self.white_ball_handle_dropped = None;
+    }
+
+    /// Energy of the balls: their moves, their heights in the air and the
+    /// spin the cue ball can still give back. The masses are relative to the
+    /// balls of the standard set.
+    pub fn energy(&self) -> f32 {
+        let spin = match (self.white_ball_handle, self.cue_spin) {
+            (Some(white), Some(cue_spin)) => cue_spin.energy() * self.ball_mass(white),
+            _ => 0.,
+        };
+        self.balls()
+            .into_iter()
+            .map(|ball| self.ball_energy(ball))
+            .sum::<f32>()
+            + spin
+    }
+
+    /// Mass of a ball, relative to the balls of the standard set.
+    fn ball_mass(&self, ball: BodyHandle) -> f32 {
+        if Some(ball) == self.white_ball_handle {
+            self.profile.cue_ball_mass
+        } else {
+            self.profile.ball_mass
+        }
+    }
+
+    /// Energy of a ball, its move and its height in the air, the spin
+    /// excepted.
+    fn ball_energy(&self, ball: BodyHandle) -> f32 {
+        let velocity = self.world.rigid_body(ball).unwrap().velocity().linear;
+        let hop = self.hops.get(&ball).map_or(0., Hop::energy);
+        (velocity.norm_squared() * 0.5 + hop) * self.ball_mass(ball)
+    }
+
+    fn stop_balls(&mut self) {
+        for ball in self.balls() {
+            self.world
+                .rigid_body_mut(ball)
+                .unwrap()
+                .set_velocity(Velocity::linear(0., 0.));
+        }
+    }
+
+    // count the events of a step in the summary of the shot
+    fn track_shot(&mut self, first_event: usize) {
+        let white = self.white_ball_handle.or(self.white_ball_handle_dropped);
+        let kind = |ball| {
+            self.ball_kind(ball)
+                .or_else(|| self.pocketed_ball(ball).map(|pocketed| pocketed.kind))
+        };
+        let mut first_hit = None;
+        let (mut ball_hits, mut cushion_hits) = (0, 0);
+        for event in self.events[first_event..].iter() {
+            match *event {
+                TableEvent::BallHit { ball1, ball2, .. } => {
+                    ball_hits += 1;
+                    if first_hit.is_none() && Some(ball1) == white {
+                        first_hit = kind(ball2);
+                    } else if first_hit.is_none() && Some(ball2) == white {
+                        first_hit = kind(ball1);
+                    }
+                }
+                TableEvent::CushionHit { .. } => cushion_hits += 1,
+                _ => {}
+            }
+        }
+        if let Some(ref mut summary) = self.shot {
+            summary.steps += 1;
+            summary.first_hit = summary.first_hit.or(first_hit);
+            summary.ball_hits += ball_hits;
+            summary.cushion_hits += cushion_hits;
+        }
+    }
+
+    /// The summary of the last shot, once, on the step the balls came to rest.
+    pub fn take_shot_complete(&mut self) -> Option<ShotSummary> {
+        self.shot_complete.take()
+    }
+
+    fn is_active(&self, handle: Option<BodyHandle>) -> bool {
+        if let Some(ball) = handle {
+            let ball_object = self.world.rigid_body(ball).unwrap();
+            return ball_object.is_active();
+        };
+        return false;
+    }
+
+    /// All the balls on the table, including the white one.
+    pub fn balls(&self) -> Vec<BodyHandle> {
+        let mut balls = Vec::with_capacity(16);
+        if let Some(ball) = self.white_ball_handle {
+            balls.push(ball);
+        }
+        if let Some(ball) = self.ball_8_handle {
+            balls.push(ball);
+        }
+        balls.extend(self.red_balls_handles.iter());
+        balls.extend(self.yellow_balls_handles.iter());
+        balls.extend(self.numbered_balls_handles.iter().map(|(_, ball)| ball));
+        balls
+    }
+
+    /// The balls are moving, the last shot is not complete.
+    pub fn has_force(&self) -> bool {
+        !self.rest.is_at_rest()
+    }
+
+    pub fn ball_kind(&self, handle: BodyHandle) -> Option<BallKind> {
+        if Some(handle) == self.white_ball_handle {
+            Some(BallKind::White)
+        } else if Some(handle) == self.ball_8_handle {
+            Some(BallKind::Black)
+        } else if self.red_balls_handles.contains(&handle) {
+            Some(BallKind::Red)
+        } else if self.yellow_balls_handles.contains(&handle) {
+            Some(BallKind::Yellow)
+        } else {
+            self.numbered_balls_handles
+                .iter()
+                .find(|(_, ball)| *ball == handle)
+                .map(|(number, _)| BallKind::Numbered(*number))
+        }
+    }
+
+    /// Center of the balls still moving.
+    fn moving_balls_center(&self) -> Option<Vector2<f32>> {
+        let moving: Vec<Vector2<f32>> = self
+            .balls()
+            .into_iter()
+            .filter(|ball| self.is_active(Some(*ball)))
+            .map(|ball| self.world.body_part(ball).position().translation.vector)
+            .collect();
+        if moving.is_empty() {
+            return None;
+        }
+        let sum = moving
+            .iter()
+            .fold(Vector2::new(0., 0.), |sum, pos| sum + pos);
+        Some(sum / moving.len() as f32)
+    }
+
+    /// Hit the cue ball. A shot too far from the center of the ball for its
+    /// force, see `MiscueLimits`, is a miscue: the cue ball gets another shot.
+    pub fn shoot(&mut self, shot: &Shot) -> ShotOutcome {
+        let intended = *shot;
+        let (outcome, played) = if self.profile.miscue.is_miscue(shot) {
+            let played = miscue(shot);
+            info!("Miscue, the shot is {}", played);
+            (ShotOutcome::Miscue(played), played)
+        } else {
+            (ShotOutcome::Clean, *shot)
+        };
+        let shot = &played;
+        let velocity = shot.velocity();
+        info!(
+            "Apply force {} {}, spin {} {}",
+            velocity.x, velocity.y, shot.spin.x, shot.spin.y
+        );
+        let ball_object = self
+            .world
+            .rigid_body_mut(self.white_ball_handle.unwrap())
+            .unwrap();
+        let vel = Velocity::linear(velocity.x, velocity.y);
+        ball_object.set_velocity(vel);
+        self.cue_spin = Some(CueSpin::new(shot));
+        self.ball_spins.clear();
+        if let Some(hop) = Hop::new(shot.jump_speed()) {
+            info!("Jump at {}", shot.jump_speed());
+            self.hops.insert(self.white_ball_handle.unwrap(), hop);
+        }
+        self.rest.wake();
+        let mut summary = ShotSummary::new(intended);
+        if let ShotOutcome::Miscue(_) = outcome {
+            summary.miscue = true;
+        }
+        self.shot = Some(summary);
+        self.shot_complete = None;
+        self.events.push(TableEvent::CueStrike {
+            speed: vel.linear.norm(),
+        });
+        outcome
+    }
+
+    /// Deviate the balls that hit each other by the friction at their
+    /// contact, from their velocities before the hit, and spin them. The
+    /// spin of an object ball changes the angle of its next rebound.
+    fn apply_throw(
+        &mut self,
+        events: &[TableEvent],
+        velocities: &HashMap<BodyHandle, Vector2<f32>>,
+    ) {
+        let friction = self.profile.ball_friction;
+        for event in events {
+            let (ball1, ball2) = match *event {
+                // the cue ball first, with its side spin
+                TableEvent::BallHit { ball1, ball2, .. }
+                    if Some(ball2) == self.white_ball_handle =>
+                {
+                    (ball2, ball1)
+                }
+                TableEvent::BallHit { ball1, ball2, .. } => (ball1, ball2),
+                TableEvent::CushionHit { ball, .. } => {
+                    if let Some(side) = self.ball_spins.remove(&ball) {
+                        let velocity = self.world.rigid_body(ball).unwrap().velocity().linear;
+                        let velocity = side_spin_rebound(velocity, side);
+                        self.set_linear_velocity(ball, velocity);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let position = |ball| self.world.body_part(ball).position().translation.vector;
+            let velocity = |ball| velocities.get(&ball).cloned().unwrap_or(na::zero());
+            let normal = position(ball2) - position(ball1);
+            if normal.norm() <= 0. {
+                continue;
+            }
+            let white = Some(ball1) == self.white_ball_handle;
+            let side = match self.cue_spin {
+                Some(cue_spin) if white => cue_spin.side_surface_speed(),
+                _ => 0.,
+            };
+            let relative = velocity(ball1) - velocity(ball2);
+            let masses = [self.ball_mass(ball1), self.ball_mass(ball2)];
+            let throw = match throw(relative, normal.normalize(), side, friction, masses) {
+                Some(throw) => throw,
+                None => continue,
+            };
+
+            let before1 = self.world.rigid_body(ball1).unwrap().velocity().linear;
+            let before2 = self.world.rigid_body(ball2).unwrap().velocity().linear;
+            let velocity1 = before1 - throw.impulse / masses[0];
+            let velocity2 = before2 + throw.impulse / masses[1];
+            self.set_linear_velocity(ball1, velocity1);
+            self.set_linear_velocity(ball2, velocity2);
+            // the side spin of the cue ball may drive the balls faster
+            let gain = ((velocity1.norm_squared() - before1.norm_squared()) * masses[0]
+                + (velocity2.norm_squared() - before2.norm_squared()) * masses[1])
+                * 0.5;
+            match self.cue_spin.as_mut() {
+                Some(cue_spin) if white => {
+                    cue_spin.spend(gain / masses[0]);
+                    cue_spin.add_side_surface_speed(throw.sides[0])
+                }
+                _ => self.add_ball_spin(ball1, side_spin(throw.sides[0], velocity1.norm())),
+            }
+            self.add_ball_spin(ball2, side_spin(throw.sides[1], velocity2.norm()));
+        }
+    }
+
+    fn add_ball_spin(&mut self, ball: BodyHandle, side: f32) {
+        let spin = self.ball_spins.entry(ball).or_insert(0.);
+        *spin = (*spin + side).max(-1.).min(1.);
+    }
+
+    fn set_linear_velocity(&mut self, ball: BodyHandle, velocity: Vector2<f32>) {
+        self.world
+            .rigid_body_mut(ball)
+            .unwrap()
+            .set_velocity(Velocity::linear(velocity.x, velocity.y));
+    }
+
+    /// Curve the cue ball of a massé while it rolls on the cloth.
+    fn apply_masse(&mut self, dt: f32) {
+        let white = match self.white_ball_handle {
+            Some(white) if !self.hops.contains_key(&white) => white,
+            _ => return,
+        };
+        let velocity = self.world.rigid_body(white).unwrap().velocity().linear;
+        let velocity = match self.cue_spin.as_mut() {
+            Some(cue_spin) => cue_spin.masse(velocity, dt),
+            None => return,
+        };
+        self.set_linear_velocity(white, velocity);
+    }
+
+    /// Move the balls in the air during `dt` seconds, they pass over the
+    /// holes, and over the balls and the cushions when they are high enough.
+    /// Return the balls that landed off the cloth.
+    fn update_hops(&mut self, dt: f32) -> Vec<BodyHandle> {
+        let radius = self.profile.ball_radius;
+        let mut off_cloth = Vec::new();
+        let hops: Vec<(BodyHandle, Hop)> = self.hops.iter().map(|(b, h)| (*b, *h)).collect();
+        for (ball, mut hop) in hops {
+            let mut blacklist = Vec::new();
+            if hop.update(dt) {
+                blacklist.push(HOLE_GROUP);
+                if hop.height > radius * BALL_CLEARANCE {
+                    blacklist.push(BALL_GROUP);
+                }
+                if hop.height > radius * CUSHION_CLEARANCE {
+                    blacklist.push(CUSHION_GROUP);
+                }
+                self.hops.insert(ball, hop);
+            } else {
+                self.hops.remove(&ball);
+                if !self.on_cloth(ball) {
+                    off_cloth.push(ball);
+                }
+            }
+            if let Some(collider) = self.ball_collider(ball) {
+                let groups = collision_groups(BALL_GROUP).with_blacklist(&blacklist);
+                self.set_collision_groups(collider, groups);
+            }
+        }
+        off_cloth
+    }
+
+    /// Height of a ball above the cloth, zero unless it is in the air.
+    pub fn height(&self, ball: BodyHandle) -> f32 {
+        self.hops.get(&ball).map_or(0., |hop| hop.height)
+    }
+
+    /// Apply the spin of the cue ball on its first contacts.
+    fn apply_spin(&mut self, events: &[TableEvent]) {
+        let (white, mut cue_spin) = match (self.white_ball_handle, self.cue_spin) {
+            (Some(white), Some(cue_spin)) => (white, cue_spin),
+            _ => return,
+        };
+        for event in events {
+            let velocity = self.world.rigid_body(white).unwrap().velocity().linear;
+            let velocity = match *event {
+                TableEvent::BallHit { ball1, ball2, .. } if ball1 == white || ball2 == white => {
+                    cue_spin.after_ball_hit(velocity)
+                }
+                TableEvent::CushionHit { ball, .. } if ball == white => {
+                    cue_spin.after_cushion_hit(velocity)
+                }
+                _ => continue,
+            };
+            self.world
+                .rigid_body_mut(white)
+                .unwrap()
+                .set_velocity(Velocity::linear(velocity.x, velocity.y));
+        }
+        self.cue_spin = Some(cue_spin);
+    }
+
+    // the center of the ball is inside the cushions, on the cloth
+    fn on_cloth(&self, handle: BodyHandle) -> bool {
+        let pos = self.world.body_part(handle).position().translation.vector;
+        let (x, y) = state::table_position(pos);
+        x > BAND && x < WIDTH + BAND && y > BAND && y < HEIGHT + BAND
+    }
+
+    // the center of the ball is inside the outer edge of the rails
+    fn in_world(&self, handle: BodyHandle) -> bool {
+        let pos = self.world.body_part(handle).position().translation.vector;
+        let (x, y) = state::table_position(pos);
+        x > 0. && x < WIDTH + 2. * BAND && y > 0. && y < HEIGHT + 2. * BAND
+    }
+
+    /// Put a ball back on the foot spot, or behind it on the long string
+    /// when it is taken, or in front of it. Return None when there is no room.
+    fn respot_ball(&mut self, kind: BallKind) -> Option<BodyHandle> {
+        let (left, right) = (MARGIN_LEFT + BORDER + BAND, MARGIN_LEFT + BORDER + BAND + WIDTH);
+        let y = MARGIN_TOP + BORDER + HEIGHT * 0.5;
+        let foot_spot = MARGIN_LEFT + BORDER + WIDTH * 0.75;
+        let behind = (0..)
+            .map(|n| foot_spot + n as f32 * self.profile.ball_radius * 2.)
+            .take_while(|x| *x < right);
+        let in_front = (1..)
+            .map(|n| foot_spot - n as f32 * self.profile.ball_radius * 2.)
+            .take_while(|x| *x > left);
+        let x = behind
+            .chain(in_front)
+            .find(|x| self.overlap(Vector2::new(*x, y), None).is_none())?;
+        Some(self.place_ball(kind, x, y))
+    }
+
+    /// Play TIME_STEP seconds.
+    ///
+    /// The step is split in sub-steps when the balls are fast, so that no
+    /// ball moves more than MAX_SUB_STEP_TRAVEL at once and none can go
+    /// through a cushion or another ball.
+    pub fn step(&mut self) {
+        let max_speed = self
+            .balls()
+            .into_iter()
+            .map(|ball| self.world.rigid_body(ball).unwrap().velocity().linear.norm())
+            .fold(0., f32::max);
+        let sub_steps = ((max_speed * TIME_STEP / MAX_SUB_STEP_TRAVEL).ceil() as usize)
+            .max(1)
+            .min(MAX_SUB_STEPS);
+
+        let first_event = self.events.len();
+        self.world.integration_parameters_mut().dt = TIME_STEP / sub_steps as f32;
+        for _ in 0..sub_steps {
+            self.sub_step();
+        }
+        self.world.integration_parameters_mut().dt = TIME_STEP;
+        self.track_shot(first_event);
+
+        let balls: Vec<(f32, f32)> = self
+            .balls()
+            .into_iter()
+            .map(|ball| (self.ball_energy(ball), self.ball_mass(ball)))
+            .collect();
+        if self.rest.update(&balls) {
+            self.stop_balls();
+            self.shot_complete = self.shot.take();
+        }
+    }
+
+    fn sub_step(&mut self) {
+        let dt = self.world.integration_parameters().dt;
+        let landed_off_cloth = self.update_hops(dt);
+        let velocities: HashMap<BodyHandle, Vector2<f32>> = self
+            .balls()
+            .into_iter()
+            .map(|ball| (ball, self.world.rigid_body(ball).unwrap().velocity().linear))
+            .collect();
+
+        self.world.step();
+
+        let mut balls = vec![];
+        let mut events = vec![];
+        for contact in self.world.contact_events() {
+            // Handle contact events.
+            if let Some(dropped) = self.handle_contact_event(contact) {
+                balls.push(dropped);
+            }
+            if let Some(event) = self.impact_event(contact, &velocities) {
+                events.push(event);
+            }
+        }
+        self.apply_throw(&events, &velocities);
+        self.apply_spin(&events);
+        self.apply_masse(dt);
+        self.events.extend(events);
+        for (ball, hole) in balls {
+            self.drop_ball(&ball, Some(hole));
+        }
+
+        for ball in self.balls() {
+            if self.in_world(ball) && !landed_off_cloth.contains(&ball) {
+                // Apply the Zgravity manually, there is no cloth under a ball in the air
+                if !self.hops.contains_key(&ball) {
+                    self.z_gravity.apply_force(&mut self.world, ball);
+                }
+            } else {
+                let speed = velocities.get(&ball).map_or(0., |velocity| velocity.norm());
+                self.drop_ball(&ball, None);
+                self.events.push(TableEvent::OffTable { ball, speed });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{BallState, TableState};
+
+    #[test]
+    fn a_respotted_ball_can_be_pocketed_again() {
+        let mut pool_table = TableState {
+            mode: GameMode::Practice,
+            balls: vec![
+                BallState {
+                    kind: BallKind::White,
+                    x: 3880.,
+                    y: 4000.,
+                },
+                BallState {
+                    kind: BallKind::Red,
+                    x: 5000.,
+                    y: 2000.,
+                },
+            ],
+            profile: PhysicsProfile::standard(),
+        }
+        .build();
+        let red = pool_table.red_balls_handles[0];
+        pool_table.drop_ball(&red, None);
+
+        let respotted = pool_table.respot_ball(BallKind::Red).unwrap();
+        assert!(!pool_table.dropped_balls().contains(&respotted));
+        pool_table.drop_ball(&respotted, None);
+        assert_eq!(pool_table.pocketed_balls().len(), 2);
+        assert!(pool_table.red_balls_handles.is_empty());
+        assert_eq!(pool_table.ball_position(respotted), None);
+    }
+}
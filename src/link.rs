@@ -0,0 +1,100 @@
+//! Shot links, a position and a shot in the URL fragment of the page:
+//!
+//! ```text
+//! #p=8+w@3880:4000,k@11640:4000+1+-+standard&s=12.50,600,0.00,0.50&replay
+//! ```
+//!
+//! `p` is the position in the compact notation, the spaces replaced by `+`,
+//! `s` the angle, force, side and follow spins of the shot, then the
+//! elevation of the cue when it is raised, and `replay` plays the shot as
+//! soon as the page is loaded.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::notation::{ParseError, Position};
+use super::shot::Shot;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShotLink {
+    pub position: Position,
+    pub shot: Option<Shot>,
+    pub replay: bool,
+}
+
+impl fmt::Display for ShotLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "p={}", self.position.to_string().replace(' ', "+"))?;
+        if let Some(ref shot) = self.shot {
+            write!(f, "&s={}", shot)?;
+        }
+        if self.replay {
+            write!(f, "&replay")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ShotLink {
+    type Err = ParseError;
+
+    /// Read a fragment, with or without the leading `#`.
+    fn from_str(fragment: &str) -> Result<Self, ParseError> {
+        let mut position = None;
+        let mut shot = None;
+        let mut replay = false;
+        for param in fragment.trim_start_matches('#').split('&') {
+            let mut parts = param.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some("p"), Some(value)) => {
+                    let value = value.replace('+', " ").replace("%20", " ");
+                    position = Some(value.parse::<Position>()?);
+                }
+                (Some("s"), Some(value)) => shot = Some(value.parse::<Shot>()?),
+                (Some("replay"), None) => replay = true,
+                // unknown parameters are left to other tools
+                _ => {}
+            }
+        }
+        let position = position.ok_or(ParseError::MissingField("position"))?;
+        Ok(ShotLink {
+            position,
+            shot,
+            replay: replay && shot.is_some(),
+        })
+    }
+}
+
+/// The link in the URL of the page, if any.
+#[cfg(target_arch = "wasm32")]
+pub fn read() -> Option<ShotLink> {
+    let location = stdweb::web::window().location()?;
+    let fragment = location.hash().ok()?;
+    if fragment.len() <= 1 {
+        return None;
+    }
+    match fragment.parse() {
+        Ok(link) => Some(link),
+        Err(err) => {
+            warn!("Ignore the shot link {}: {}", fragment, err);
+            None
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read() -> Option<ShotLink> {
+    None
+}
+
+/// Put the link in the URL of the page, without a new entry in the history.
+#[cfg(target_arch = "wasm32")]
+pub fn write(link: &ShotLink) {
+    let url = format!("#{}", link);
+    stdweb::web::window().history().replace_state((), "", Some(&url));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(link: &ShotLink) {
+    info!("Shot link: #{}", link);
+}
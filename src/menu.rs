@@ -0,0 +1,384 @@
+use quicksilver::{
+    geom::{Rectangle, Vector},
+    graphics::{
+        Background::{Col, Img},
+        Color, Font, FontStyle,
+    },
+    input::{ButtonState, Key},
+    lifecycle::{Asset, Window},
+    Result,
+};
+
+use super::gamepad::GamepadState;
+use super::input::{self, Action, Bindings, ACTIONS};
+use super::physics::{PhysicsProfile, PROFILES};
+use super::sound::Mixer;
+use super::GameMode;
+
+pub const FONT_FILE: &str = "DejaVuSans.ttf";
+
+const FONT_SIZE: f32 = 28.;
+const LINE_HEIGHT: f32 = 48.;
+const MENU_WIDTH: f32 = 480.;
+
+const SENSITIVITY_STEP: f32 = 1.25;
+const MIN_COARSE_MULTIPLIER: f32 = 2.;
+const MAX_COARSE_MULTIPLIER: f32 = 40.;
+const MIN_FINE_MULTIPLIER: f32 = 0.02;
+const MAX_FINE_MULTIPLIER: f32 = 0.8;
+const VOLUME_STEP: f32 = 0.1;
+
+pub const COARSE_MULTIPLIER: f32 = 10.;
+pub const FINE_MULTIPLIER: f32 = 0.2;
+
+/// Settings that can be changed from the pause menu.
+pub struct GameSettings {
+    pub mode: GameMode,
+    // index of the physics profile of the new tables, in PROFILES
+    pub table: usize,
+    pub aim_assist: bool,
+    // multiplier applied on force and angle steps while Coarse is held
+    pub coarse_multiplier: f32,
+    // multiplier applied on force and angle steps while Fine is held
+    pub fine_multiplier: f32,
+    pub debug_overlay: bool,
+    pub mixer: Mixer,
+    pub bindings: Bindings,
+}
+
+impl GameSettings {
+    pub fn new() -> Self {
+        GameSettings {
+            mode: GameMode::EightBall,
+            table: 0,
+            aim_assist: true,
+            coarse_multiplier: COARSE_MULTIPLIER,
+            fine_multiplier: FINE_MULTIPLIER,
+            debug_overlay: false,
+            mixer: Mixer::new(),
+            bindings: Bindings::load(),
+        }
+    }
+
+    pub fn profile(&self) -> PhysicsProfile {
+        PROFILES[self.table]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MenuItem {
+    Resume,
+    NewGame,
+    GameMode,
+    Table,
+    Drill,
+    EditTable,
+    AimAssist,
+    CoarseSensitivity,
+    FineSensitivity,
+    DebugOverlay,
+    Sound,
+    Volume,
+    Controls,
+    // items of the controls page
+    Binding(Action),
+    ResetBindings,
+    Back,
+}
+
+const MENU_ITEMS: [MenuItem; 13] = [
+    MenuItem::Resume,
+    MenuItem::NewGame,
+    MenuItem::GameMode,
+    MenuItem::Table,
+    MenuItem::Drill,
+    MenuItem::EditTable,
+    MenuItem::AimAssist,
+    MenuItem::CoarseSensitivity,
+    MenuItem::FineSensitivity,
+    MenuItem::DebugOverlay,
+    MenuItem::Sound,
+    MenuItem::Volume,
+    MenuItem::Controls,
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Page {
+    Main,
+    Controls,
+}
+
+/// What the game has to do after the menu has been updated.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuAction {
+    Resume,
+    NewGame,
+    // index of the drill in the library
+    StartDrill(usize),
+    EditTable,
+}
+
+pub struct PauseMenu {
+    page: Page,
+    selected: usize,
+    // the action waiting for a key to be bound
+    capturing: Option<Action>,
+    // names of the loaded drills, and the selected one
+    drills: Vec<String>,
+    drill: usize,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        PauseMenu {
+            page: Page::Main,
+            selected: 0,
+            capturing: None,
+            drills: Vec::new(),
+            drill: 0,
+        }
+    }
+
+    pub fn has_drills(&self) -> bool {
+        !self.drills.is_empty()
+    }
+
+    pub fn set_drills(&mut self, drills: Vec<String>) {
+        self.drills = drills;
+        self.drill = 0;
+    }
+
+    /// The menu is waiting for a key, the key must not be used by the game.
+    pub fn is_capturing(&self) -> bool {
+        self.capturing.is_some()
+    }
+
+    fn items(&self) -> Vec<MenuItem> {
+        match self.page {
+            Page::Main => MENU_ITEMS.to_vec(),
+            Page::Controls => {
+                let mut items: Vec<MenuItem> =
+                    ACTIONS.iter().map(|action| MenuItem::Binding(*action)).collect();
+                items.push(MenuItem::ResetBindings);
+                items.push(MenuItem::Back);
+                items
+            }
+        }
+    }
+
+    fn open(&mut self, page: Page) {
+        self.page = page;
+        self.selected = 0;
+    }
+
+    pub fn update(
+        &mut self,
+        window: &mut Window,
+        gamepad: &GamepadState,
+        settings: &mut GameSettings,
+    ) -> Option<MenuAction> {
+        if let Some(action) = self.capturing {
+            if let Some(key) = input::pressed_key(window) {
+                settings.bindings.bind(action, key);
+                settings.bindings.save();
+                self.capturing = None;
+            }
+            return None;
+        }
+
+        let keyboard = window.keyboard();
+        let items = self.items();
+
+        if keyboard[Key::Down] == ButtonState::Pressed || gamepad.down {
+            self.selected = (self.selected + 1) % items.len();
+        }
+        if keyboard[Key::Up] == ButtonState::Pressed || gamepad.up {
+            self.selected = (self.selected + items.len() - 1) % items.len();
+        }
+
+        let item = items[self.selected];
+        if keyboard[Key::Right] == ButtonState::Pressed || gamepad.right {
+            self.change(item, settings, true);
+        }
+        if keyboard[Key::Left] == ButtonState::Pressed || gamepad.left {
+            self.change(item, settings, false);
+        }
+
+        // Activate on release, otherwise the game receive the Return key
+        // still held on the next frame, and shoot.
+        if keyboard[Key::Return] == ButtonState::Released || gamepad.activate {
+            match item {
+                MenuItem::Resume => return Some(MenuAction::Resume),
+                MenuItem::NewGame => return Some(MenuAction::NewGame),
+                MenuItem::Drill if self.has_drills() => {
+                    return Some(MenuAction::StartDrill(self.drill));
+                }
+                MenuItem::EditTable => return Some(MenuAction::EditTable),
+                MenuItem::Controls => self.open(Page::Controls),
+                MenuItem::Back => self.open(Page::Main),
+                MenuItem::Binding(action) => self.capturing = Some(action),
+                MenuItem::ResetBindings => {
+                    settings.bindings = Bindings::new();
+                    settings.bindings.save();
+                }
+                _ => self.change(item, settings, true),
+            }
+        }
+        None
+    }
+
+    fn change(&mut self, item: MenuItem, settings: &mut GameSettings, forward: bool) {
+        match item {
+            MenuItem::Drill if self.has_drills() => {
+                let count = self.drills.len();
+                self.drill = if forward {
+                    (self.drill + 1) % count
+                } else {
+                    (self.drill + count - 1) % count
+                };
+            }
+            MenuItem::GameMode => {
+                settings.mode = if forward {
+                    settings.mode.next()
+                } else {
+                    settings.mode.previous()
+                };
+            }
+            MenuItem::Table => {
+                let count = PROFILES.len();
+                settings.table = if forward {
+                    (settings.table + 1) % count
+                } else {
+                    (settings.table + count - 1) % count
+                };
+            }
+            MenuItem::AimAssist => settings.aim_assist = !settings.aim_assist,
+            MenuItem::DebugOverlay => settings.debug_overlay = !settings.debug_overlay,
+            MenuItem::Sound => settings.mixer.muted = !settings.mixer.muted,
+            MenuItem::Volume => {
+                let step = if forward { VOLUME_STEP } else { -VOLUME_STEP };
+                settings.mixer.volume = (settings.mixer.volume + step).max(0.).min(1.);
+            }
+            MenuItem::CoarseSensitivity => {
+                settings.coarse_multiplier = step_multiplier(
+                    settings.coarse_multiplier,
+                    forward,
+                    MIN_COARSE_MULTIPLIER,
+                    MAX_COARSE_MULTIPLIER,
+                );
+            }
+            MenuItem::FineSensitivity => {
+                settings.fine_multiplier = step_multiplier(
+                    settings.fine_multiplier,
+                    forward,
+                    MIN_FINE_MULTIPLIER,
+                    MAX_FINE_MULTIPLIER,
+                );
+            }
+            MenuItem::Resume
+            | MenuItem::NewGame
+            | MenuItem::Drill
+            | MenuItem::EditTable
+            | MenuItem::Controls
+            | MenuItem::Binding(_)
+            | MenuItem::ResetBindings
+            | MenuItem::Back => {}
+        }
+    }
+
+    fn label(&self, item: MenuItem, settings: &GameSettings) -> String {
+        match item {
+            MenuItem::Resume => "Resume".to_string(),
+            MenuItem::NewGame => format!("New game ({})", settings.mode.name()),
+            MenuItem::GameMode => format!("Game mode: < {} >", settings.mode.name()),
+            MenuItem::Table => format!("Table: < {} >", settings.profile().name),
+            MenuItem::Drill => match self.drills.get(self.drill) {
+                Some(name) => format!("Drill: < {} >", name),
+                None => "Drill: none".to_string(),
+            },
+            MenuItem::AimAssist => format!("Aim assist: {}", on_off(settings.aim_assist)),
+            MenuItem::CoarseSensitivity => format!(
+                "Coarse aim ({}): x{:.1}",
+                settings.bindings.label(Action::Coarse),
+                settings.coarse_multiplier
+            ),
+            MenuItem::FineSensitivity => format!(
+                "Fine aim ({}): x{:.2}",
+                settings.bindings.label(Action::Fine),
+                settings.fine_multiplier
+            ),
+            MenuItem::DebugOverlay => format!("Debug overlay: {}", on_off(settings.debug_overlay)),
+            MenuItem::Sound => format!("Sound: {}", on_off(!settings.mixer.muted)),
+            MenuItem::Volume => format!("Volume: {:.0}%", settings.mixer.volume * 100.),
+            MenuItem::EditTable => "Edit the table".to_string(),
+            MenuItem::Controls => "Controls".to_string(),
+            MenuItem::Binding(action) if self.capturing == Some(action) => {
+                format!("{}: press a key...", action.name())
+            }
+            MenuItem::Binding(action) => {
+                format!("{}: {}", action.name(), settings.bindings.label(action))
+            }
+            MenuItem::ResetBindings => "Reset defaults".to_string(),
+            MenuItem::Back => "Back".to_string(),
+        }
+    }
+
+    pub fn draw(
+        &self,
+        window: &mut Window,
+        font: &mut Asset<Font>,
+        settings: &GameSettings,
+    ) -> Result<()> {
+        let screen = window.screen_size();
+        window.draw(
+            &Rectangle::new((0., 0.), screen),
+            Col(Color::BLACK.with_alpha(0.6)),
+        );
+
+        let items = self.items();
+        let height = LINE_HEIGHT * items.len() as f32;
+        let top = (screen.y - height) / 2.;
+        let left = (screen.x - MENU_WIDTH) / 2.;
+
+        let selected = Rectangle::new(
+            (left, top + LINE_HEIGHT * self.selected as f32),
+            (MENU_WIDTH, LINE_HEIGHT),
+        );
+        window.draw(&selected, Col(Color::WHITE.with_alpha(0.2)));
+
+        let labels: Vec<String> = items
+            .iter()
+            .map(|item| self.label(*item, settings))
+            .collect();
+
+        font.execute(|font| {
+            let style = FontStyle::new(FONT_SIZE, Color::WHITE);
+            for (idx, label) in labels.iter().enumerate() {
+                let image = font.render(label, &style)?;
+                let center = Vector::new(
+                    screen.x / 2.,
+                    top + LINE_HEIGHT * (idx as f32 + 0.5),
+                );
+                window.draw(&image.area().with_center(center), Img(&image));
+            }
+            Ok(())
+        })
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn step_multiplier(value: f32, forward: bool, min: f32, max: f32) -> f32 {
+    let value = if forward {
+        value * SENSITIVITY_STEP
+    } else {
+        value / SENSITIVITY_STEP
+    };
+    value.max(min).min(max)
+}
@@ -0,0 +1,142 @@
+//! Miscues: the tip of the cue slides off the cue ball when it hits it
+//! too far from its center.
+
+use ncollide2d::math::Vector as Vector2;
+
+use super::shot::Shot;
+use super::MAX_FORCE;
+
+// part of the force the cue ball gets from a miscue, picked between them
+const MIN_POWER: f32 = 0.1;
+const MAX_POWER: f32 = 0.5;
+// largest deviation of the cue ball, in degrees
+const MAX_DEVIATION: f32 = 5.;
+
+/// Largest tip offsets without a miscue, in the unit disk of `Shot::spin`.
+/// A hard shot miscues closer to the center.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MiscueLimits {
+    // for a soft shot
+    pub offset: f32,
+    // for a shot at MAX_FORCE
+    pub hard_offset: f32,
+}
+
+impl MiscueLimits {
+    pub fn max_offset(&self, force: f32) -> f32 {
+        let hardness = (force / MAX_FORCE).max(0.).min(1.);
+        self.offset + (self.hard_offset - self.offset) * hardness
+    }
+
+    pub fn is_miscue(&self, shot: &Shot) -> bool {
+        shot.spin.norm() > self.max_offset(shot.force)
+    }
+}
+
+/// The shot the cue ball really gets from a miscue: a part of the force,
+/// a little off the aim, and no spin.
+///
+/// It is random, but the same shot always miscues the same way, so that a
+/// shot link replays it and the simulations are repeatable.
+pub fn miscue(shot: &Shot) -> Shot {
+    let mut random = Random::new(shot);
+    let power = MIN_POWER + (MAX_POWER - MIN_POWER) * random.next();
+    let deviation = MAX_DEVIATION * (random.next() * 2. - 1.);
+    Shot {
+        angle: shot.angle + deviation,
+        force: shot.force * power,
+        spin: Vector2::new(0., 0.),
+        elevation: 0.,
+    }
+}
+
+// xorshift, seeded by the shot
+struct Random(u32);
+
+impl Random {
+    fn new(shot: &Shot) -> Self {
+        let seed = shot.angle.to_bits()
+            ^ shot.force.to_bits().rotate_left(8)
+            ^ shot.spin.x.to_bits().rotate_left(16)
+            ^ shot.spin.y.to_bits().rotate_left(24)
+            ^ shot.elevation.to_bits().rotate_left(4);
+        Random(seed | 1)
+    }
+
+    // between 0 and 1
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shots() -> Vec<Shot> {
+        let mut shots = Vec::new();
+        for angle in (0..360).step_by(15) {
+            for force in (100..=MAX_FORCE as usize).step_by(100) {
+                for &(side, follow) in [(1., 0.), (-0.6, 0.8), (0., -1.)].iter() {
+                    shots.push(Shot {
+                        angle: angle as f32,
+                        force: force as f32,
+                        spin: Vector2::new(side, follow),
+                        elevation: 0.,
+                    });
+                }
+            }
+        }
+        shots
+    }
+
+    #[test]
+    fn the_same_shot_miscues_the_same_way() {
+        for shot in shots() {
+            assert_eq!(miscue(&shot), miscue(&shot));
+        }
+        let shot = shots()[0];
+        let other = Shot {
+            angle: shot.angle + 1.,
+            ..shot
+        };
+        assert_ne!(miscue(&shot).force, miscue(&other).force);
+    }
+
+    #[test]
+    fn a_miscue_keeps_a_part_of_the_force_close_to_the_aim() {
+        for shot in shots() {
+            let played = miscue(&shot);
+            let power = played.force / shot.force;
+            assert!(
+                power >= MIN_POWER - 1e-6 && power <= MAX_POWER + 1e-6,
+                "{} kept {}",
+                shot,
+                power
+            );
+            assert!((played.angle - shot.angle).abs() <= MAX_DEVIATION + 1e-3);
+            assert_eq!(played.spin, Vector2::new(0., 0.));
+            assert_eq!(played.elevation, 0.);
+        }
+    }
+
+    #[test]
+    fn hard_shots_miscue_closer_to_the_center() {
+        let limits = MiscueLimits {
+            offset: 0.9,
+            hard_offset: 0.7,
+        };
+        let shot = |force| Shot {
+            angle: 0.,
+            force,
+            spin: Vector2::new(0.8, 0.),
+            elevation: 0.,
+        };
+        assert!(!limits.is_miscue(&shot(0.)));
+        assert!(limits.is_miscue(&shot(MAX_FORCE)));
+        assert!((limits.max_offset(MAX_FORCE * 2.) - limits.hard_offset).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,399 @@
+//! Compact text notation of a position, to paste in bug reports or URLs.
+//!
+//! A position is five fields separated by spaces:
+//!
+//! ```text
+//! 8 w@3880:4000,k@11520:4000,r@10560:4000,y@11040:3720 1 ry standard
+//! ```
+//!
+//! - the table: `8` for eight ball, `9` for nine ball, `p` for practice,
+//! - the balls, separated by commas, a kind and a position on the table:
+//!   `w` the cue ball, `k` the black, `r` a red, `y` a yellow and
+//!   `n1` to `n15` the numbered balls; the position is `x:y` in the units
+//!   of the table, where a ball is 480 wide, 420 with the small balls
+//!   profile, `-` when the table is empty,
+//! - the player who has to shoot, from 1,
+//! - the groups of the players: `-` when they are not assigned yet,
+//!   `ry` when the first player has the reds, `yr` otherwise,
+//! - the physics profile of the table, its name with `-` for the spaces;
+//!   the older positions without it are on a standard table.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::game::{Game, Group};
+use super::physics::{PhysicsProfile, PROFILES};
+use super::state::{BallState, LayoutError, TableState, REST_TOLERANCE};
+use super::{BallKind, GameMode, PoolTable};
+
+/// Why a position can not be read.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParseError {
+    MissingField(&'static str),
+    TrailingField(String),
+    UnknownTable(String),
+    UnknownBall(String),
+    BadCoordinates(String),
+    BadTurn(String),
+    BadGroups(String),
+    UnknownProfile(String),
+    BadShot(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingField(field) => write!(f, "missing {}", field),
+            ParseError::TrailingField(field) => write!(f, "unexpected field {:?}", field),
+            ParseError::UnknownTable(table) => write!(f, "unknown table {:?}", table),
+            ParseError::UnknownBall(ball) => write!(f, "unknown ball {:?}", ball),
+            ParseError::BadCoordinates(ball) => write!(f, "bad coordinates in {:?}", ball),
+            ParseError::BadTurn(turn) => write!(f, "bad player {:?}", turn),
+            ParseError::BadGroups(groups) => write!(f, "bad groups {:?}", groups),
+            ParseError::UnknownProfile(profile) => write!(f, "unknown profile {:?}", profile),
+            ParseError::BadShot(shot) => write!(f, "bad shot {:?}", shot),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The balls on the table and the state of the game.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Position {
+    pub table: TableState,
+    // the player who has to shoot, from 0
+    pub current_player: usize,
+    pub groups: Option<[Group; 2]>,
+}
+
+impl Position {
+    pub fn of(pool_table: &PoolTable, game: &Game) -> Self {
+        Position {
+            table: TableState::of(pool_table),
+            current_player: game.current_player,
+            groups: game.groups,
+        }
+    }
+
+    /// The table and the game of the position, nothing pocketed yet.
+    pub fn build(&self) -> Result<(PoolTable, Game), LayoutError> {
+        let pool_table = self
+            .table
+            .build_checked_within(self.table.profile, REST_TOLERANCE)?;
+        let mut game = Game::new(self.table.mode);
+        game.current_player = self.current_player.min(game.players() - 1);
+        game.groups = self.groups;
+        Ok((pool_table, game))
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = match self.table.mode {
+            GameMode::EightBall => "8",
+            GameMode::NineBall => "9",
+            GameMode::Practice => "p",
+        };
+        let balls: Vec<String> = self
+            .table
+            .balls
+            .iter()
+            .map(|ball| {
+                format!(
+                    "{}@{}:{}",
+                    ball_code(ball.kind),
+                    ball.x.round().max(0.),
+                    ball.y.round().max(0.)
+                )
+            })
+            .collect();
+        let balls = if balls.is_empty() {
+            "-".to_string()
+        } else {
+            balls.join(",")
+        };
+        let groups = match self.groups {
+            Some([first, _]) => match first {
+                Group::Red => "ry",
+                Group::Yellow => "yr",
+            },
+            None => "-",
+        };
+        write!(
+            f,
+            "{} {} {} {} {}",
+            table,
+            balls,
+            self.current_player + 1,
+            groups,
+            profile_code(&self.table.profile)
+        )
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParseError;
+
+    fn from_str(notation: &str) -> Result<Self, ParseError> {
+        let mut fields = notation.split_whitespace();
+
+        let table = fields.next().ok_or(ParseError::MissingField("table"))?;
+        let mode = match table {
+            "8" => GameMode::EightBall,
+            "9" => GameMode::NineBall,
+            "p" => GameMode::Practice,
+            _ => return Err(ParseError::UnknownTable(table.to_string())),
+        };
+
+        let balls = fields.next().ok_or(ParseError::MissingField("balls"))?;
+        let balls = if balls == "-" {
+            Vec::new()
+        } else {
+            balls
+                .split(',')
+                .map(parse_ball)
+                .collect::<Result<Vec<BallState>, ParseError>>()?
+        };
+
+        let turn = fields.next().ok_or(ParseError::MissingField("player"))?;
+        let current_player = match turn.parse::<usize>() {
+            Ok(player) if player == 1 || player == 2 => player - 1,
+            _ => return Err(ParseError::BadTurn(turn.to_string())),
+        };
+
+        let groups = fields.next().ok_or(ParseError::MissingField("groups"))?;
+        let groups = match groups {
+            "-" => None,
+            "ry" => Some([Group::Red, Group::Yellow]),
+            "yr" => Some([Group::Yellow, Group::Red]),
+            _ => return Err(ParseError::BadGroups(groups.to_string())),
+        };
+
+        let profile = match fields.next() {
+            Some(code) => PROFILES
+                .iter()
+                .find(|profile| profile_code(profile) == code)
+                .cloned()
+                .ok_or_else(|| ParseError::UnknownProfile(code.to_string()))?,
+            None => PhysicsProfile::standard(),
+        };
+
+        if let Some(field) = fields.next() {
+            return Err(ParseError::TrailingField(field.to_string()));
+        }
+
+        Ok(Position {
+            table: TableState {
+                mode,
+                balls,
+                profile,
+            },
+            current_player,
+            groups,
+        })
+    }
+}
+
+fn profile_code(profile: &PhysicsProfile) -> String {
+    profile.name.replace(' ', "-")
+}
+
+fn ball_code(kind: BallKind) -> String {
+    match kind {
+        BallKind::White => "w".to_string(),
+        BallKind::Black => "k".to_string(),
+        BallKind::Red => "r".to_string(),
+        BallKind::Yellow => "y".to_string(),
+        BallKind::Numbered(number) => format!("n{}", number),
+    }
+}
+
+fn parse_ball(ball: &str) -> Result<BallState, ParseError> {
+    let mut parts = ball.splitn(2, '@');
+    let code = parts.next().unwrap_or("");
+    let kind = match code {
+        "w" => BallKind::White,
+        "k" => BallKind::Black,
+        "r" => BallKind::Red,
+        "y" => BallKind::Yellow,
+        _ if code.starts_with('n') => match code[1..].parse::<u8>() {
+            Ok(number) if number >= 1 && number <= 15 => BallKind::Numbered(number),
+            _ => return Err(ParseError::UnknownBall(ball.to_string())),
+        },
+        _ => return Err(ParseError::UnknownBall(ball.to_string())),
+    };
+
+    let coordinates = parts
+        .next()
+        .ok_or_else(|| ParseError::BadCoordinates(ball.to_string()))?;
+    let mut coordinates = coordinates.splitn(2, ':');
+    let mut coordinate = || -> Result<f32, ParseError> {
+        coordinates
+            .next()
+            .and_then(|value| value.parse::<u32>().ok())
+            .map(|value| value as f32)
+            .ok_or_else(|| ParseError::BadCoordinates(ball.to_string()))
+    };
+    let x = coordinate()?;
+    let y = coordinate()?;
+    Ok(BallState { kind, x, y })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Overlap, BAND, HEIGHT};
+
+    fn reparse(position: &Position) -> Position {
+        position.to_string().parse().unwrap()
+    }
+
+    #[test]
+    fn format_and_parse_round_trip() {
+        let position = Position {
+            table: TableState {
+                mode: GameMode::EightBall,
+                balls: vec![
+                    BallState {
+                        kind: BallKind::White,
+                        x: 3880.,
+                        y: 4000.,
+                    },
+                    BallState {
+                        kind: BallKind::Numbered(12),
+                        x: 11520.,
+                        y: 400.,
+                    },
+                ],
+                profile: PROFILES[2],
+            },
+            current_player: 1,
+            groups: Some([Group::Yellow, Group::Red]),
+        };
+        assert_eq!(
+            position.to_string(),
+            "8 w@3880:4000,n12@11520:400 2 yr bar-table"
+        );
+        assert_eq!(reparse(&position), position);
+    }
+
+    #[test]
+    fn every_profile_round_trips() {
+        for profile in PROFILES.iter() {
+            let position = Position {
+                table: TableState {
+                    mode: GameMode::Practice,
+                    balls: Vec::new(),
+                    profile: *profile,
+                },
+                current_player: 0,
+                groups: None,
+            };
+            assert_eq!(reparse(&position).table.profile, *profile);
+        }
+    }
+
+    #[test]
+    fn coordinates_are_separated_by_a_colon() {
+        let position: Position = "p w@3880:4000 1 -".parse().unwrap();
+        assert_eq!(
+            position.table.balls,
+            vec![BallState {
+                kind: BallKind::White,
+                x: 3880.,
+                y: 4000.,
+            }]
+        );
+        for ball in ["w@3880.4000", "w@3880", "w@3880:4000:10", "w@3880:-4000"].iter() {
+            let notation = format!("p {} 1 -", ball);
+            assert_eq!(
+                notation.parse::<Position>(),
+                Err(ParseError::BadCoordinates(ball.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn positions_without_a_profile_are_standard() {
+        let position: Position = "p w@3880:4000 1 -".parse().unwrap();
+        assert_eq!(position.table.profile, PhysicsProfile::standard());
+        assert_eq!(
+            "p - 1 - pool".parse::<Position>(),
+            Err(ParseError::UnknownProfile("pool".to_string()))
+        );
+    }
+
+    #[test]
+    fn racks_build_after_a_round_trip() {
+        for mode in [GameMode::EightBall, GameMode::NineBall, GameMode::Practice].iter() {
+            let mut pool_table = PoolTable::new();
+            pool_table.initialze_world(*mode);
+            let position = Position::of(&pool_table, &Game::new(*mode));
+            let (rebuilt, _) = reparse(&position).build().unwrap();
+            let balls = TableState::of(&rebuilt).balls;
+            assert_eq!(balls.len(), position.table.balls.len());
+            for (ball, original) in balls.iter().zip(position.table.balls.iter()) {
+                assert_eq!(ball.kind, original.kind);
+                assert!((ball.x - original.x).abs() <= 0.5);
+                assert!((ball.y - original.y).abs() <= 0.5);
+            }
+        }
+    }
+
+    // the balls at rest touch the cushions and each other, the rounding
+    // must not make them overlap
+    #[test]
+    fn frozen_balls_build_after_a_round_trip() {
+        let radius = PhysicsProfile::standard().ball_radius;
+        let y = BAND + HEIGHT - radius + 0.4;
+        let position = Position {
+            table: TableState {
+                mode: GameMode::Practice,
+                balls: vec![
+                    BallState {
+                        kind: BallKind::White,
+                        x: 5000.6,
+                        y,
+                    },
+                    BallState {
+                        kind: BallKind::Red,
+                        x: 5000.6 + radius * 2. - 0.6,
+                        y,
+                    },
+                    BallState {
+                        kind: BallKind::Yellow,
+                        x: 9000.,
+                        y: BAND + radius - 0.6,
+                    },
+                ],
+                profile: PhysicsProfile::standard(),
+            },
+            current_player: 0,
+            groups: None,
+        };
+        assert!(reparse(&position).build().is_ok());
+    }
+
+    #[test]
+    fn a_second_black_ball_is_rejected() {
+        let black = |x| BallState {
+            kind: BallKind::Black,
+            x,
+            y: 4000.,
+        };
+        let position = Position {
+            table: TableState {
+                mode: GameMode::EightBall,
+                balls: vec![black(5000.), black(9000.)],
+                profile: PhysicsProfile::standard(),
+            },
+            current_player: 0,
+            groups: None,
+        };
+        let error = reparse(&position).build().err().unwrap();
+        assert_eq!(error.ball, black(9000.));
+        assert_eq!(error.overlap, Overlap::Duplicate);
+    }
+}
@@ -0,0 +1,121 @@
+//! Physical parameters of the tables and of their ball sets.
+
+use super::miscue::MiscueLimits;
+use super::BALL_SIZE;
+
+/// The cloth, the cushions and the balls of a table, picked when the
+/// `PoolTable` is created.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PhysicsProfile {
+    pub name: &'static str,
+    // part of the speed of the balls lost every second on the cloth,
+    // a slow cloth brakes more
+    pub cloth_drag: f32,
+    pub cushion_restitution: f32,
+    pub cushion_friction: f32,
+    pub ball_restitution: f32,
+    // friction between slow balls, it throws the object balls off the
+    // line of centers, see `throw::throw`
+    pub ball_friction: f32,
+    pub ball_radius: f32,
+    // masses relative to the balls of the standard set
+    pub ball_mass: f32,
+    pub cue_ball_mass: f32,
+    // how far from the center the cue can hit the cue ball
+    pub miscue: MiscueLimits,
+}
+
+pub const PROFILES: [PhysicsProfile; 4] = [
+    PhysicsProfile {
+        name: "standard",
+        cloth_drag: 0.86,
+        cushion_restitution: 0.95,
+        cushion_friction: 0.,
+        ball_restitution: 0.4,
+        ball_friction: 0.1,
+        ball_radius: BALL_SIZE,
+        ball_mass: 1.,
+        cue_ball_mass: 1.,
+        miscue: MiscueLimits {
+            offset: 0.9,
+            hard_offset: 0.7,
+        },
+    },
+    // a new worsted cloth, the balls run much longer
+    PhysicsProfile {
+        name: "fast cloth",
+        cloth_drag: 0.6,
+        cushion_restitution: 0.95,
+        cushion_friction: 0.,
+        ball_restitution: 0.4,
+        ball_friction: 0.1,
+        ball_radius: BALL_SIZE,
+        ball_mass: 1.,
+        cue_ball_mass: 1.,
+        miscue: MiscueLimits {
+            offset: 0.9,
+            hard_offset: 0.7,
+        },
+    },
+    // coin-op table: slow nap cloth, dead rubber, dirty balls that throw
+    // more, worn cue tips that miscue sooner, and a heavier cue ball so the
+    // machine can tell it from the object balls
+    PhysicsProfile {
+        name: "bar table",
+        cloth_drag: 1.1,
+        cushion_restitution: 0.8,
+        cushion_friction: 0.1,
+        ball_restitution: 0.4,
+        ball_friction: 0.13,
+        ball_radius: BALL_SIZE,
+        ball_mass: 1.,
+        cue_ball_mass: 1.25,
+        miscue: MiscueLimits {
+            offset: 0.8,
+            hard_offset: 0.55,
+        },
+    },
+    // smaller and lighter balls, with lively cushions
+    PhysicsProfile {
+        name: "small balls",
+        cloth_drag: 0.75,
+        cushion_restitution: 0.97,
+        cushion_friction: 0.,
+        ball_restitution: 0.5,
+        ball_friction: 0.09,
+        ball_radius: BALL_SIZE * 0.875,
+        ball_mass: 0.8,
+        cue_ball_mass: 0.8,
+        miscue: MiscueLimits {
+            offset: 0.9,
+            hard_offset: 0.7,
+        },
+    },
+];
+
+impl PhysicsProfile {
+    pub fn standard() -> Self {
+        PROFILES[0]
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        PROFILES.iter().find(|profile| profile.name == name).cloned()
+    }
+
+    /// Density of the physics bodies of the balls, the masses do not
+    /// change with the radius.
+    pub fn ball_density(&self, cue_ball: bool) -> f32 {
+        let mass = if cue_ball {
+            self.cue_ball_mass
+        } else {
+            self.ball_mass
+        };
+        mass * (BALL_SIZE / self.ball_radius).powi(2)
+    }
+}
+
+impl Default for PhysicsProfile {
+    fn default() -> Self {
+        PhysicsProfile::standard()
+    }
+}
@@ -0,0 +1,127 @@
+//! When the balls of a shot come to rest.
+
+// below this speed a lone ball is still, in units per second
+const REST_SPEED: f32 = 150.;
+// kinetic energy under which a ball of mass 1 is still
+const REST_ENERGY: f32 = 0.5 * REST_SPEED * REST_SPEED;
+// the balls must stay still a quarter of second, at 60 steps per second
+const REST_STEPS: usize = 15;
+
+/// Decide when the balls are at rest, from their kinetic energies after
+/// every step: the energy of every ball must stay under REST_ENERGY, in
+/// proportion to its mass, during REST_STEPS steps.
+pub struct RestDetector {
+    still_steps: usize,
+    at_rest: bool,
+}
+
+impl RestDetector {
+    pub fn new() -> Self {
+        RestDetector {
+            still_steps: 0,
+            at_rest: true,
+        }
+    }
+
+    /// The balls are set in motion by a shot.
+    pub fn wake(&mut self) {
+        self.still_steps = 0;
+        self.at_rest = false;
+    }
+
+    /// Return true only on the step the balls come to rest, from the
+    /// kinetic energy and the mass of every ball.
+    pub fn update(&mut self, balls: &[(f32, f32)]) -> bool {
+        if self.at_rest {
+            return false;
+        }
+        let still = balls
+            .iter()
+            .all(|&(energy, mass)| energy < REST_ENERGY * mass);
+        if still {
+            self.still_steps += 1;
+        } else {
+            self.still_steps = 0;
+        }
+        self.at_rest = self.still_steps >= REST_STEPS;
+        self.at_rest
+    }
+
+    pub fn is_at_rest(&self) -> bool {
+        self.at_rest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STILL: [(f32, f32); 2] = [(0., 1.), (REST_ENERGY * 0.5, 1.)];
+    const MOVING: [(f32, f32); 2] = [(0., 1.), (REST_ENERGY * 2., 1.)];
+
+    fn still_steps(rest: &mut RestDetector, steps: usize) -> Vec<bool> {
+        (0..steps).map(|_| rest.update(&STILL)).collect()
+    }
+
+    #[test]
+    fn at_rest_until_woken() {
+        let mut rest = RestDetector::new();
+        assert!(rest.is_at_rest());
+        assert!(!rest.update(&MOVING));
+        assert!(rest.is_at_rest());
+        rest.wake();
+        assert!(!rest.is_at_rest());
+    }
+
+    #[test]
+    fn true_once_on_the_step_the_balls_come_to_rest() {
+        let mut rest = RestDetector::new();
+        rest.wake();
+        let steps = still_steps(&mut rest, REST_STEPS * 2);
+        let rested: Vec<usize> = (0..steps.len()).filter(|&step| steps[step]).collect();
+        assert_eq!(rested, vec![REST_STEPS - 1]);
+        assert!(rest.is_at_rest());
+    }
+
+    #[test]
+    fn a_move_restarts_the_still_time() {
+        let mut rest = RestDetector::new();
+        rest.wake();
+        still_steps(&mut rest, REST_STEPS - 1);
+        assert!(!rest.update(&MOVING));
+        assert!(!still_steps(&mut rest, REST_STEPS - 1).contains(&true));
+        assert!(rest.update(&STILL));
+    }
+
+    #[test]
+    fn wake_restarts_the_still_time() {
+        let mut rest = RestDetector::new();
+        rest.wake();
+        still_steps(&mut rest, REST_STEPS - 1);
+        rest.wake();
+        assert!(!still_steps(&mut rest, REST_STEPS - 1).contains(&true));
+        assert!(rest.update(&STILL));
+    }
+
+    // many slow balls are still, even when their energies add up over
+    // the energy of a single moving ball
+    #[test]
+    fn every_ball_is_compared_on_its_own() {
+        let mut rest = RestDetector::new();
+        rest.wake();
+        let slow = [(REST_ENERGY * 0.9, 1.); 16];
+        let rested = (0..REST_STEPS).any(|_| rest.update(&slow));
+        assert!(rested);
+    }
+
+    #[test]
+    fn heavier_balls_are_still_with_more_energy() {
+        let heavy = [(REST_ENERGY * 1.2, 1.25)];
+        let light = [(REST_ENERGY * 1.2, 1.)];
+        let mut rest = RestDetector::new();
+        rest.wake();
+        assert!((0..REST_STEPS).any(|_| rest.update(&heavy)));
+        rest.wake();
+        assert!(!(0..REST_STEPS).any(|_| rest.update(&light)));
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ncollide2d::math::Vector as Vector2;
+
+use super::notation::ParseError;
+use super::{BallKind, PocketedBall, MAX_FORCE};
+
+/// Highest elevation of the cue, in degrees.
+pub const MAX_ELEVATION: f32 = 60.;
+// part of the speed driven into the slate that it gives back upwards
+const JUMP_TRANSFER: f32 = 0.3;
+
+/// A shot of the cue ball.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Shot {
+    // direction of the cue, in degrees
+    pub angle: f32,
+    pub force: f32,
+    // where the cue hits the ball, in the unit disk: x for the side spin
+    // (positive to the right), y for the follow (positive) or draw.
+    pub spin: Vector2<f32>,
+    // angle of the cue above the table, in degrees: the cue ball jumps,
+    // and curves with side spin (massé)
+    pub elevation: f32,
+}
+
+impl Shot {
+    pub fn direction(&self) -> Vector2<f32> {
+        let rot = self.angle.to_radians();
+        Vector2::new(rot.cos(), rot.sin())
+    }
+
+    /// Speed given by the cue, on the table and into the slate.
+    pub fn speed(&self) -> f32 {
+        self.force.powf(1.5)
+    }
+
+    /// Velocity given to the cue ball on the table, an elevated cue drives
+    /// a part of it into the slate.
+    pub fn velocity(&self) -> Vector2<f32> {
+        self.direction() * self.speed() * self.elevation.to_radians().cos()
+    }
+
+    /// Upward speed of the cue ball, bounced back by the slate.
+    pub fn jump_speed(&self) -> f32 {
+        self.speed() * self.elevation.to_radians().sin() * JUMP_TRANSFER
+    }
+}
+
+impl fmt::Display for Shot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.2},{:.0},{:.2},{:.2}",
+            self.angle, self.force, self.spin.x, self.spin.y
+        )?;
+        if self.elevation > 0. {
+            write!(f, ",{:.1}", self.elevation)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Shot {
+    type Err = ParseError;
+
+    /// Read an angle, a force, the side and follow spins and optionally the
+    /// elevation, separated by commas. The shot of a link is clamped to the
+    /// shots the player can play.
+    fn from_str(shot: &str) -> Result<Self, ParseError> {
+        let values = shot
+            .split(',')
+            .map(|value| value.parse::<f32>().ok().filter(|value| value.is_finite()))
+            .collect::<Option<Vec<f32>>>()
+            .ok_or_else(|| ParseError::BadShot(shot.to_string()))?;
+        let (angle, force, side, follow, elevation) = match values.as_slice() {
+            [angle, force, side, follow] => (*angle, *force, *side, *follow, 0.),
+            [angle, force, side, follow, elevation] => (*angle, *force, *side, *follow, *elevation),
+            _ => return Err(ParseError::BadShot(shot.to_string())),
+        };
+        Ok(Shot {
+            angle,
+            force: clamp_force(force),
+            spin: clamp_spin(Vector2::new(side, follow)),
+            elevation: clamp_elevation(elevation),
+        })
+    }
+}
+
+/// Keep the tip of the cue on the ball.
+pub fn clamp_spin(spin: Vector2<f32>) -> Vector2<f32> {
+    let norm = spin.norm();
+    if norm > 1. {
+        spin / norm
+    } else {
+        spin
+    }
+}
+
+pub fn clamp_elevation(elevation: f32) -> f32 {
+    elevation.max(0.).min(MAX_ELEVATION)
+}
+
+/// Keep the force in the range of the cue, stronger shots would go through
+/// the cushions.
+pub fn clamp_force(force: f32) -> f32 {
+    force.max(0.).min(MAX_FORCE)
+}
+
+/// What the cue did to the cue ball.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ShotOutcome {
+    Clean,
+    // the tip slid off the ball, with the shot the cue ball really got
+    Miscue(Shot),
+}
+
+/// What happened during a shot, published once when the balls are at rest.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShotSummary {
+    // the shot of the player, see `miscue` for the shot played
+    pub shot: Shot,
+    // the cue slid off the ball, a foul
+    pub miscue: bool,
+    pub steps: usize,
+    // first ball touched by the cue ball, none when it touched none
+    pub first_hit: Option<BallKind>,
+    pub ball_hits: usize,
+    pub cushion_hits: usize,
+    // balls pocketed or off the table, in order
+    pub pocketed: Vec<PocketedBall>,
+}
+
+impl ShotSummary {
+    pub fn new(shot: Shot) -> Self {
+        ShotSummary {
+            shot,
+            miscue: false,
+            steps: 0,
+            first_hit: None,
+            ball_hits: 0,
+            cushion_hits: 0,
+            pocketed: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_parse_round_trip() {
+        let shot: Shot = "12.50,800,0.30,-0.40,10.0".parse().unwrap();
+        assert_eq!(shot.to_string().parse::<Shot>().unwrap(), shot);
+        assert_eq!(shot.elevation, 10.);
+    }
+
+    #[test]
+    fn values_that_are_not_finite_are_rejected() {
+        for shot in [
+            "NaN,800,0,0",
+            "0,NaN,0,0",
+            "0,800,NaN,0",
+            "0,800,0,-nan",
+            "0,800,0,0,NaN",
+            "inf,800,0,0",
+            "0,inf,0,0",
+            "0,800,-inf,0",
+            "0,800,0,0,infinity",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                shot.parse::<Shot>(),
+                Err(ParseError::BadShot(shot.to_string())),
+                "{}",
+                shot
+            );
+        }
+    }
+
+    #[test]
+    fn the_force_is_clamped_to_the_cue() {
+        let shot: Shot = "0,1e9,0,0".parse().unwrap();
+        assert_eq!(shot.force, MAX_FORCE);
+        let shot: Shot = "0,-100,0,0".parse().unwrap();
+        assert_eq!(shot.force, 0.);
+    }
+
+    #[test]
+    fn the_spin_and_elevation_are_clamped() {
+        let shot: Shot = "0,800,3,4,90".parse().unwrap();
+        assert!((shot.spin - Vector2::new(0.6, 0.8)).norm() < 1e-6);
+        assert_eq!(shot.elevation, MAX_ELEVATION);
+    }
+}
@@ -0,0 +1,33 @@
+//! Headless play of a shot, without a window.
+
+use super::shot::Shot;
+use super::{PoolTable, TableEvent};
+
+/// Two minutes of play at 60 steps per second, the balls are always at
+/// rest before.
+pub const MAX_STEPS: usize = 120 * 60;
+
+/// Play a shot until every ball is at rest, or MAX_STEPS.
+///
+/// `on_step` receives the step number, the table after the step and the
+/// events of the step. Return the number of steps played.
+pub fn play<F>(pool_table: &mut PoolTable, shot: &Shot, mut on_step: F) -> usize
+where
+    F: FnMut(usize, &PoolTable, &[TableEvent]),
+{
+    if pool_table.white_ball_handle.is_none() {
+        return 0;
+    }
+    pool_table.shoot(shot);
+    let mut steps = 0;
+    while steps < MAX_STEPS {
+        pool_table.step();
+        let events = pool_table.take_events();
+        on_step(steps, pool_table, &events);
+        steps += 1;
+        if !pool_table.has_force() {
+            break;
+        }
+    }
+    steps
+}
@@ -0,0 +1,245 @@
+//! Sound effects triggered by the table events.
+//!
+//! The `Mixer` turns events in `SoundCue`s without touching any audio
+//! device, the `SoundBank` plays them.
+
+use quicksilver::{lifecycle::Asset, sound::Sound, Result};
+
+use super::TableEvent;
+
+// impacts slower than that are silent, balls at rest still touch each others.
+const MIN_IMPACT_SPEED: f32 = 300.;
+const MAX_SOUNDS_PER_STEP: usize = 4;
+
+const MIN_PITCH: f32 = 0.85;
+const MAX_PITCH: f32 = 1.18;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SoundKind {
+    Cue,
+    Click,
+    Cushion,
+    Pocket,
+}
+
+impl SoundKind {
+    fn name(&self) -> &'static str {
+        match self {
+            SoundKind::Cue => "cue",
+            SoundKind::Click => "click",
+            SoundKind::Cushion => "cushion",
+            SoundKind::Pocket => "pocket",
+        }
+    }
+
+    // speed that plays the sound at full volume
+    fn reference_speed(&self) -> f32 {
+        match self {
+            SoundKind::Cue => 30000.,
+            SoundKind::Click => 15000.,
+            SoundKind::Cushion => 15000.,
+            SoundKind::Pocket => 8000.,
+        }
+    }
+}
+
+/// A sound to play.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SoundCue {
+    pub kind: SoundKind,
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+impl SoundCue {
+    /// The sound of an event, louder and higher for faster impacts.
+    pub fn from_event(event: &TableEvent) -> Option<SoundCue> {
+        let (kind, speed) = match *event {
+            TableEvent::CueStrike { speed } => (SoundKind::Cue, speed),
+            TableEvent::BallHit { speed, .. } => (SoundKind::Click, speed),
+            TableEvent::CushionHit { speed, .. } => (SoundKind::Cushion, speed),
+            TableEvent::Pocketed { speed, .. } => (SoundKind::Pocket, speed),
+            // the ball falls on the floor, far from the table
+            TableEvent::OffTable { .. } => return None,
+        };
+        if speed < MIN_IMPACT_SPEED && kind != SoundKind::Pocket {
+            return None;
+        }
+        let strength = (speed / kind.reference_speed()).max(0.).min(1.);
+        Some(SoundCue {
+            kind,
+            // the ear is not linear, a soft hit must still be heard
+            volume: strength.sqrt(),
+            pitch: MIN_PITCH + (MAX_PITCH - MIN_PITCH) * strength,
+        })
+    }
+}
+
+/// Volume settings applied on every sound.
+pub struct Mixer {
+    pub muted: bool,
+    pub volume: f32,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Mixer {
+            muted: false,
+            volume: 0.8,
+        }
+    }
+
+    /// Sounds to play for the events of a step, the loudest first.
+    pub fn cues(&self, events: &[TableEvent]) -> Vec<SoundCue> {
+        if self.muted || self.volume <= 0. {
+            return Vec::new();
+        }
+        let volume = self.volume.min(1.);
+        let mut cues: Vec<SoundCue> = events
+            .iter()
+            .filter_map(SoundCue::from_event)
+            .map(|cue| SoundCue {
+                volume: cue.volume * volume,
+                ..cue
+            })
+            .collect();
+        cues.sort_by(|a, b| b.volume.partial_cmp(&a.volume).unwrap());
+        cues.truncate(MAX_SOUNDS_PER_STEP);
+        cues
+    }
+}
+
+/// The sounds files, every sound exists in three pitches.
+pub struct SoundBank {
+    sounds: Vec<(SoundKind, [Asset<Sound>; 3])>,
+}
+
+impl SoundBank {
+    pub fn new() -> Self {
+        let kinds = [
+            SoundKind::Cue,
+            SoundKind::Click,
+            SoundKind::Cushion,
+            SoundKind::Pocket,
+        ];
+        let load = |kind: SoundKind, pitch: &str| {
+            Asset::new(Sound::load(format!("sounds/{}-{}.wav", kind.name(), pitch)))
+        };
+        let sounds = kinds
+            .iter()
+            .map(|kind| {
+                (
+                    *kind,
+                    [load(*kind, "low"), load(*kind, "mid"), load(*kind, "high")],
+                )
+            })
+            .collect();
+        SoundBank { sounds }
+    }
+
+    pub fn play(&mut self, cue: &SoundCue) -> Result<()> {
+        let variant = if cue.pitch < 0.93 {
+            0
+        } else if cue.pitch > 1.09 {
+            2
+        } else {
+            1
+        };
+        let volume = cue.volume;
+        for (kind, sounds) in self.sounds.iter_mut() {
+            if *kind == cue.kind {
+                sounds[variant].execute(|sound| {
+                    sound.set_volume(volume);
+                    sound.play()
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nphysics2d::object::BodyHandle;
+
+    fn ball_hit(speed: f32) -> TableEvent {
+        TableEvent::BallHit {
+            ball1: BodyHandle::ground(),
+            ball2: BodyHandle::ground(),
+            speed,
+        }
+    }
+
+    #[test]
+    fn faster_impacts_are_louder_and_higher() {
+        let soft = SoundCue::from_event(&ball_hit(1000.)).unwrap();
+        let hard = SoundCue::from_event(&ball_hit(10000.)).unwrap();
+        assert_eq!(soft.kind, SoundKind::Click);
+        assert!(soft.volume < hard.volume);
+        assert!(soft.pitch < hard.pitch);
+        assert!(soft.pitch >= MIN_PITCH && hard.pitch <= MAX_PITCH);
+    }
+
+    #[test]
+    fn the_volume_and_pitch_stop_at_the_reference_speed() {
+        let speed = SoundKind::Click.reference_speed();
+        let cue = SoundCue::from_event(&ball_hit(speed * 3.)).unwrap();
+        assert_eq!(cue, SoundCue::from_event(&ball_hit(speed)).unwrap());
+        assert_eq!(cue.volume, 1.);
+        assert!((cue.pitch - MAX_PITCH).abs() < 1e-6);
+    }
+
+    #[test]
+    fn slow_impacts_are_silent_but_not_the_pockets() {
+        assert_eq!(SoundCue::from_event(&ball_hit(MIN_IMPACT_SPEED * 0.5)), None);
+        let pocketed = TableEvent::Pocketed {
+            ball: BodyHandle::ground(),
+            hole: BodyHandle::ground(),
+            speed: 10.,
+        };
+        assert!(SoundCue::from_event(&pocketed).is_some());
+        let off_table = TableEvent::OffTable {
+            ball: BodyHandle::ground(),
+            speed: 10000.,
+        };
+        assert_eq!(SoundCue::from_event(&off_table), None);
+    }
+
+    #[test]
+    fn a_muted_mixer_plays_nothing() {
+        let events = [TableEvent::CueStrike { speed: 20000. }, ball_hit(5000.)];
+        let mut mixer = Mixer::new();
+        assert_eq!(mixer.cues(&events).len(), 2);
+        mixer.muted = true;
+        assert!(mixer.cues(&events).is_empty());
+        mixer.muted = false;
+        mixer.volume = 0.;
+        assert!(mixer.cues(&events).is_empty());
+    }
+
+    #[test]
+    fn the_mixer_keeps_the_loudest_sounds() {
+        let events: Vec<_> = (1..10).map(|n| ball_hit(n as f32 * 1000.)).collect();
+        let cues = Mixer::new().cues(&events);
+        assert_eq!(cues.len(), MAX_SOUNDS_PER_STEP);
+        assert!(cues.windows(2).all(|pair| pair[0].volume >= pair[1].volume));
+        assert_eq!(cues[0], {
+            let loudest = SoundCue::from_event(&ball_hit(9000.)).unwrap();
+            SoundCue {
+                volume: loudest.volume * 0.8,
+                ..loudest
+            }
+        });
+    }
+
+    #[test]
+    fn the_volume_is_clamped() {
+        let events = [ball_hit(SoundKind::Click.reference_speed())];
+        let mixer = Mixer {
+            muted: false,
+            volume: 2.,
+        };
+        assert_eq!(mixer.cues(&events)[0].volume, 1.);
+    }
+}
@@ -0,0 +1,212 @@
+//! Spin of the balls: the cue ball's from the shot, and the side spin
+//! the balls keep after a hit.
+
+use ncollide2d::math::Vector as Vector2;
+
+use super::shot::{clamp_spin, Shot};
+
+// part of the cue ball speed given back by the spin after the first hit
+const FOLLOW_TRANSFER: f32 = 0.5;
+// deviation of the cue ball on the first cushion, in degrees, at full side spin
+const SIDE_SPIN_ANGLE: f32 = 20.;
+// surface speed of the cue ball at full side spin, relative to its speed
+const SIDE_SURFACE_SPEED: f32 = 0.5;
+// part of the speed of the cue ball turned into a curve, at full side
+// spin with a vertical cue
+const MASSE_TRANSFER: f32 = 0.5;
+// time for the cloth to turn the spin of a massé into the curve, in seconds
+const MASSE_TIME: f32 = 0.5;
+// a solid ball hit s radii off its center spins with a surface speed of
+// 5/2 s times the speed of the cue, a fifth of its square is the energy
+const SPIN_ENERGY: f32 = 1.25;
+
+/// Spin of the cue ball, applied on its first contacts.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CueSpin {
+    direction: Vector2<f32>,
+    speed: f32,
+    spin: Vector2<f32>,
+    // speed of the curve of a massé not given yet, to the right of the shot
+    masse: f32,
+    // energy of the spin not given to the cue ball yet, for a unit mass
+    energy: f32,
+    ball_hit: bool,
+    cushion_hit: bool,
+}
+
+impl CueSpin {
+    pub fn new(shot: &Shot) -> Self {
+        CueSpin {
+            direction: shot.direction(),
+            speed: shot.velocity().norm(),
+            spin: shot.spin,
+            masse: shot.spin.x * shot.elevation.to_radians().sin() * shot.speed() * MASSE_TRANSFER,
+            energy: SPIN_ENERGY * shot.spin.norm_squared() * shot.speed().powi(2),
+            ball_hit: false,
+            cushion_hit: false,
+        }
+    }
+
+    /// Energy the spin can still give to the cue ball, for a unit mass.
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    /// Take from the energy of the spin what the cue ball gains from it,
+    /// for a unit mass.
+    pub fn spend(&mut self, energy: f32) {
+        self.energy = (self.energy - energy.max(0.)).max(0.);
+    }
+
+    // the velocity `to` given by the spin to the cue ball moving at `from`,
+    // no faster than what is left of the energy of the spin
+    fn give(&mut self, from: Vector2<f32>, to: Vector2<f32>) -> Vector2<f32> {
+        let gain = (to.norm_squared() - from.norm_squared()) * 0.5;
+        if gain <= self.energy {
+            self.spend(gain);
+            return to;
+        }
+        let speed = (from.norm_squared() + 2. * self.energy).sqrt();
+        self.energy = 0.;
+        to.normalize() * speed
+    }
+
+    /// Velocity of the cue ball after a massé curved it during `dt` seconds
+    /// on the cloth, until its first contact.
+    pub fn masse(&mut self, velocity: Vector2<f32>, dt: f32) -> Vector2<f32> {
+        if self.ball_hit || self.cushion_hit {
+            self.masse = 0.;
+        }
+        let rate = self.speed * MASSE_TRANSFER / MASSE_TIME;
+        let curve = self.masse.max(-rate * dt).min(rate * dt);
+        self.masse -= curve;
+        // right of the direction of the shot
+        let curve = Vector2::new(-self.direction.y, self.direction.x) * curve;
+        self.give(velocity, velocity + curve)
+    }
+
+    /// Velocity of the cue ball after it hit the first object ball,
+    /// the follow pushes it forward and the draw pulls it back. The spin
+    /// has no more effect on the energy after that.
+    pub fn after_ball_hit(&mut self, velocity: Vector2<f32>) -> Vector2<f32> {
+        if self.ball_hit {
+            return velocity;
+        }
+        self.ball_hit = true;
+        let follow = self.direction * self.speed * self.spin.y * FOLLOW_TRANSFER;
+        let velocity = self.give(velocity, velocity + follow);
+        self.energy = 0.;
+        velocity
+    }
+
+    /// Velocity of the cue ball after the first cushion, the side spin
+    /// changes the angle of the rebound.
+    pub fn after_cushion_hit(&mut self, velocity: Vector2<f32>) -> Vector2<f32> {
+        if self.cushion_hit {
+            return velocity;
+        }
+        self.cushion_hit = true;
+        side_spin_rebound(velocity, self.spin.x)
+    }
+
+    /// Surface speed of the side spin of the cue ball at its first hit,
+    /// see `throw::throw`.
+    pub fn side_surface_speed(&self) -> f32 {
+        if self.ball_hit {
+            return 0.;
+        }
+        self.spin.x * self.speed * SIDE_SURFACE_SPEED
+    }
+
+    /// The first hit gave some side spin to the cue ball, or took some.
+    pub fn add_side_surface_speed(&mut self, surface_speed: f32) {
+        if self.speed > 0. {
+            self.spin.x += surface_speed / (self.speed * SIDE_SURFACE_SPEED);
+            self.spin = clamp_spin(self.spin);
+        }
+    }
+}
+
+/// Side spin of a ball running at `speed`, from the surface speed of its spin.
+pub fn side_spin(surface_speed: f32, speed: f32) -> f32 {
+    if speed <= 0. {
+        return 0.;
+    }
+    (surface_speed / (speed * SIDE_SURFACE_SPEED))
+        .max(-1.)
+        .min(1.)
+}
+
+/// Velocity of a ball after a cushion, deviated by its side spin.
+pub fn side_spin_rebound(velocity: Vector2<f32>, side: f32) -> Vector2<f32> {
+    let angle = (side * SIDE_SPIN_ANGLE).to_radians();
+    let (sin, cos) = angle.sin_cos();
+    Vector2::new(
+        velocity.x * cos - velocity.y * sin,
+        velocity.x * sin + velocity.y * cos,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shot(spin: Vector2<f32>, elevation: f32) -> Shot {
+        Shot {
+            angle: 0.,
+            force: 800.,
+            spin,
+            elevation,
+        }
+    }
+
+    #[test]
+    fn a_shot_without_spin_has_no_spin_energy() {
+        let mut cue_spin = CueSpin::new(&shot(Vector2::new(0., 0.), 0.));
+        assert_eq!(cue_spin.energy(), 0.);
+        let velocity = Vector2::new(100., 0.);
+        assert_eq!(cue_spin.after_ball_hit(velocity), velocity);
+    }
+
+    #[test]
+    fn the_follow_takes_its_energy_from_the_spin() {
+        let shot = shot(Vector2::new(0., 1.), 0.);
+        let mut cue_spin = CueSpin::new(&shot);
+        let before = cue_spin.energy();
+        // a full hit stops the cue ball, the follow pushes it forward
+        let velocity = cue_spin.after_ball_hit(Vector2::new(0., 0.));
+        assert!(velocity.x > 0. && velocity.y.abs() < 1e-3);
+        assert!(velocity.norm_squared() * 0.5 <= before);
+        assert_eq!(cue_spin.energy(), 0.);
+    }
+
+    #[test]
+    fn the_spin_gives_no_more_than_its_energy() {
+        let shot = shot(Vector2::new(0., 0.2), 0.);
+        let mut cue_spin = CueSpin::new(&shot);
+        let before = cue_spin.energy();
+        // a thin cut, the cue ball keeps most of its speed
+        let velocity = shot.velocity() * 0.9;
+        let after = cue_spin.after_ball_hit(velocity);
+        let gain = (after.norm_squared() - velocity.norm_squared()) * 0.5;
+        assert!(gain <= before * 1.001, "{} from {}", gain, before);
+        assert!(after.x > velocity.x);
+    }
+
+    #[test]
+    fn the_masse_curves_with_the_energy_of_the_spin() {
+        let shot = shot(Vector2::new(1., 0.), 45.);
+        let mut cue_spin = CueSpin::new(&shot);
+        let start = cue_spin.energy();
+        let mut velocity = shot.velocity();
+        for _ in 0..120 {
+            let before = cue_spin.energy() + velocity.norm_squared() * 0.5;
+            velocity = cue_spin.masse(velocity, 1. / 60.);
+            let after = cue_spin.energy() + velocity.norm_squared() * 0.5;
+            assert!(after <= before * 1.0001, "{} to {}", before, after);
+        }
+        // to the right of the shot, downwards on the screen
+        assert!(velocity.y > 0.);
+        assert!(cue_spin.energy() < start);
+    }
+}
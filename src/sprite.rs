@@ -0,0 +1,243 @@
+//! Software rasterization of the balls and of the table textures.
+//!
+//! Everything is rendered to RGBA buffers, so it does not depend on
+//! the graphic backend.
+
+use nalgebra::{UnitQuaternion, Vector3};
+
+use super::BallKind;
+
+pub type Rgb = [u8; 3];
+
+pub const CLOTH_COLOR: Rgb = [0x28, 0x6b, 0x31];
+pub const BAND_COLOR: Rgb = [0x0b, 0x45, 0x16];
+pub const BORDER_COLOR: Rgb = [0x4a, 0x2c, 0x14];
+pub const HOLE_COLOR: Rgb = [0x22, 0x22, 0x22];
+
+const AMBIENT: f32 = 0.35;
+const SPECULAR: f32 = 0.7;
+const SHININESS: i32 = 40;
+
+// cosine of the angular radius of the number spot
+const NUMBER_SPOT: f32 = 0.8;
+// cosine of the angular radius of the cue ball spot
+const CUE_SPOT: f32 = 0.95;
+const STRIPE_HALF_WIDTH: f32 = 0.5;
+
+// 3x5 digits, one row of 3 bits per line, top to bottom.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// How a ball looks like.
+pub struct BallStyle {
+    pub color: Rgb,
+    pub stripe: bool,
+    pub number: Option<u8>,
+    // small dot of the cue ball
+    pub spot: Option<Rgb>,
+}
+
+impl BallStyle {
+    pub fn of(kind: BallKind) -> BallStyle {
+        let plain = |color| BallStyle {
+            color,
+            stripe: false,
+            number: None,
+            spot: None,
+        };
+        match kind {
+            BallKind::White => BallStyle {
+                spot: Some([0xcc, 0x22, 0x22]),
+                ..plain([0xf4, 0xf1, 0xe6])
+            },
+            BallKind::Black => BallStyle {
+                number: Some(8),
+                ..plain([0x11, 0x11, 0x11])
+            },
+            BallKind::Red => plain([0xd0, 0x1c, 0x1c]),
+            BallKind::Yellow => plain([0xf2, 0xc6, 0x1a]),
+            BallKind::Numbered(number) => BallStyle {
+                stripe: number > 8,
+                number: Some(number),
+                ..plain(numbered_color(number))
+            },
+        }
+    }
+}
+
+/// Color of a numbered ball, the stripes share the color of `number - 8`.
+pub fn numbered_color(number: u8) -> Rgb {
+    match number {
+        1 | 9 => [0xf2, 0xc6, 0x1a],
+        2 | 10 => [0x1c, 0x3f, 0xb0],
+        3 | 11 => [0xd0, 0x1c, 0x1c],
+        4 | 12 => [0x5a, 0x1f, 0x8c],
+        5 | 13 => [0xf0, 0x7a, 0x10],
+        6 | 14 => [0x16, 0x80, 0x3a],
+        7 | 15 => [0x80, 0x10, 0x10],
+        _ => [0x11, 0x11, 0x11],
+    }
+}
+
+/// Render a lit ball of `size` pixels.
+///
+/// `orientation` rotates the ball frame to the world frame, where x goes
+/// right, y goes down and z goes in the table; the viewer is on the negative z.
+pub fn ball(size: u32, style: &BallStyle, orientation: &UnitQuaternion<f32>) -> Vec<u8> {
+    let mut data = vec![0; (size * size * 4) as usize];
+
+    let to_local = orientation.inverse();
+    let light = Vector3::new(-0.35, -0.45, -0.82).normalize();
+    let half_way = (light + Vector3::new(0., 0., -1.)).normalize();
+    let half_size = size as f32 * 0.5;
+
+    for py in 0..size {
+        for px in 0..size {
+            let x = (px as f32 + 0.5) / half_size - 1.;
+            let y = (py as f32 + 0.5) / half_size - 1.;
+            let distance = (x * x + y * y).sqrt();
+
+            // antialiased edge
+            let alpha = ((1. - distance) * half_size + 0.5).max(0.).min(1.);
+            if alpha <= 0. {
+                continue;
+            }
+
+            let z = -(1. - distance.min(1.).powi(2)).sqrt();
+            let normal = Vector3::new(x, y, z);
+            let local = to_local * normal;
+
+            let base = surface_color(style, &local);
+
+            let diffuse = normal.dot(&light).max(0.);
+            let shade = AMBIENT + (1. - AMBIENT) * diffuse;
+            let specular = normal.dot(&half_way).max(0.).powi(SHININESS) * SPECULAR;
+
+            let idx = ((py * size + px) * 4) as usize;
+            for channel in 0..3 {
+                let value = base[channel] as f32 * shade + 255. * specular;
+                data[idx + channel] = value.max(0.).min(255.) as u8;
+            }
+            data[idx + 3] = (alpha * 255.) as u8;
+        }
+    }
+    data
+}
+
+// color of the ball at a point of its surface, in the ball frame.
+fn surface_color(style: &BallStyle, local: &Vector3<f32>) -> Rgb {
+    const WHITE: Rgb = [0xf4, 0xf1, 0xe6];
+    const INK: Rgb = [0x11, 0x11, 0x11];
+
+    if let Some(number) = style.number {
+        if -local.z > NUMBER_SPOT {
+            let radius = (1. - NUMBER_SPOT * NUMBER_SPOT).sqrt();
+            let u = local.x / radius;
+            let v = local.y / radius;
+            if digit_pixel(number, u, v) {
+                return INK;
+            }
+            return WHITE;
+        }
+    }
+    if let Some(spot) = style.spot {
+        if -local.z > CUE_SPOT {
+            return spot;
+        }
+    }
+    if style.stripe && local.y.abs() > STRIPE_HALF_WIDTH {
+        return WHITE;
+    }
+    style.color
+}
+
+// whether the point (u, v) of the number spot, in [-1, 1], is inked.
+fn digit_pixel(number: u8, u: f32, v: f32) -> bool {
+    let digits: Vec<usize> = if number >= 10 {
+        vec![(number / 10) as usize, (number % 10) as usize]
+    } else {
+        vec![number as usize]
+    };
+    // one empty column between two digits
+    let columns = 4 * digits.len() - 1;
+    let cell = 1.5 / (columns.max(5) as f32);
+
+    let col = ((u + columns as f32 * cell * 0.5) / cell).floor();
+    let row = ((v + 5. * cell * 0.5) / cell).floor();
+    if col < 0. || row < 0. || col >= columns as f32 || row >= 5. {
+        return false;
+    }
+    let (col, row) = (col as usize, row as usize);
+    if col % 4 == 3 {
+        return false;
+    }
+    let glyph = DIGITS[digits[col / 4]];
+    glyph[row] & (0b100 >> (col % 4)) != 0
+}
+
+/// Felt texture, with a light pool in the middle of the table.
+pub fn cloth(width: u32, height: u32) -> Vec<u8> {
+    let mut noise = Noise::new(0x2f6b_3108);
+    texture(width, height, |x, y| {
+        let dx = x - 0.5;
+        let dy = y - 0.5;
+        let light = 1.08 - 0.35 * (dx * dx + dy * dy);
+        let grain = 1. + noise.next() * 0.05;
+        scale(CLOTH_COLOR, light * grain)
+    })
+}
+
+/// Wood texture, the grain goes along the x axis.
+pub fn wood(width: u32, height: u32) -> Vec<u8> {
+    let mut noise = Noise::new(0x4a2c_1400);
+    texture(width, height, |x, y| {
+        let wave = (x * 9.).sin() * 0.04 + (x * 23.).sin() * 0.015;
+        let ring = ((y + wave) * 70.).sin();
+        let grain = 1. + ring * 0.12 + noise.next() * 0.03;
+        scale(BORDER_COLOR, grain)
+    })
+}
+
+fn texture<F: FnMut(f32, f32) -> Rgb>(width: u32, height: u32, mut pixel: F) -> Vec<u8> {
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for py in 0..height {
+        for px in 0..width {
+            let color = pixel(px as f32 / width as f32, py as f32 / height as f32);
+            data.extend_from_slice(&color);
+            data.push(0xff);
+        }
+    }
+    data
+}
+
+fn scale(color: Rgb, factor: f32) -> Rgb {
+    let channel = |value: u8| (value as f32 * factor).max(0.).min(255.) as u8;
+    [channel(color[0]), channel(color[1]), channel(color[2])]
+}
+
+// xorshift noise, textures must be the same on every run.
+struct Noise(u32);
+
+impl Noise {
+    fn new(seed: u32) -> Self {
+        Noise(seed)
+    }
+
+    // value in [-1, 1]
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 as f32 / std::u32::MAX as f32) * 2. - 1.
+    }
+}
@@ -0,0 +1,165 @@
+use ncollide2d::math::Vector as Vector2;
+use serde_derive::{Deserialize, Serialize};
+
+use std::fmt;
+
+use super::physics::PhysicsProfile;
+use super::{
+    BallKind, GameMode, Overlap, PoolTable, BALL_SIZE, BORDER, MARGIN_LEFT, MARGIN_TOP,
+};
+
+// positions are relative to the top left corner of the table,
+// the margins of the world are not saved.
+const ORIGIN_X: f32 = MARGIN_LEFT + BORDER;
+const ORIGIN_Y: f32 = MARGIN_TOP + BORDER;
+
+/// How far the balls at rest sink in each other and in the cushions, a
+/// saved layout may be rounded by as much.
+pub const REST_TOLERANCE: f32 = BALL_SIZE * 0.05;
+
+/// A position of the world, relative to the table.
+pub fn table_position(pos: Vector2<f32>) -> (f32, f32) {
+    (pos.x - ORIGIN_X, pos.y - ORIGIN_Y)
+}
+
+/// A ball of a layout that can not be placed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LayoutError {
+    pub ball: BallState,
+    pub overlap: Overlap,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the {:?} ball at ({}, {}) is {}",
+            self.ball.kind,
+            self.ball.x,
+            self.ball.y,
+            self.overlap.name()
+        )
+    }
+}
+
+/// Position of a ball at rest.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BallState {
+    pub kind: BallKind,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Snapshot of the balls on the table, the pocketed ones are omitted.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TableState {
+    pub mode: GameMode,
+    pub balls: Vec<BallState>,
+    // saved by name, the layouts without one are on a standard table
+    #[serde(default, with = "profile_name")]
+    pub profile: PhysicsProfile,
+}
+
+impl TableState {
+    pub fn of(pool_table: &PoolTable) -> Self {
+        let balls = pool_table
+            .balls()
+            .into_iter()
+            .filter_map(|ball| {
+                let kind = pool_table.ball_kind(ball)?;
+                let pos = pool_table.world.body_part(ball).position().translation.vector;
+                let (x, y) = table_position(pos);
+                Some(BallState { kind, x, y })
+            })
+            .collect();
+        TableState {
+            mode: pool_table.mode,
+            balls,
+            profile: pool_table.profile,
+        }
+    }
+
+    /// A new table of the profile of the layout, with the balls at rest on
+    /// their positions.
+    pub fn build(&self) -> PoolTable {
+        self.build_with(self.profile)
+    }
+
+    pub fn build_with(&self, profile: PhysicsProfile) -> PoolTable {
+        let mut pool_table = PoolTable::with_profile(profile);
+        pool_table.mode = self.mode;
+        pool_table.initialize_holes();
+        pool_table.initialize_bounds();
+        for ball in self.balls.iter() {
+            pool_table.place_ball(ball.kind, ORIGIN_X + ball.x, ORIGIN_Y + ball.y);
+        }
+        pool_table
+    }
+
+    /// Like build, but a ball inside a cushion, a hole or another ball
+    /// rejects the layout, as does a second cue ball or black ball.
+    pub fn build_checked(&self) -> Result<PoolTable, LayoutError> {
+        self.build_checked_with(self.profile)
+    }
+
+    pub fn build_checked_with(&self, profile: PhysicsProfile) -> Result<PoolTable, LayoutError> {
+        self.build_checked_within(profile, 0.)
+    }
+
+    /// Like build_checked, but the balls may sink `tolerance` in the
+    /// cushions and in each other, as the balls at rest do.
+    pub fn build_checked_within(
+        &self,
+        profile: PhysicsProfile,
+        tolerance: f32,
+    ) -> Result<PoolTable, LayoutError> {
+        let mut pool_table = PoolTable::with_profile(profile);
+        pool_table.mode = self.mode;
+        pool_table.initialize_holes();
+        pool_table.initialize_bounds();
+        for ball in self.balls.iter() {
+            let pos = Vector2::new(ORIGIN_X + ball.x, ORIGIN_Y + ball.y);
+            let duplicate = match ball.kind {
+                BallKind::White => pool_table.white_ball_handle.is_some(),
+                BallKind::Black => pool_table.ball_8_handle.is_some(),
+                _ => false,
+            };
+            let overlap = if duplicate {
+                Some(Overlap::Duplicate)
+            } else {
+                pool_table.overlap_within(pos, None, tolerance)
+            };
+            if let Some(overlap) = overlap {
+                return Err(LayoutError {
+                    ball: *ball,
+                    overlap,
+                });
+            }
+            pool_table.place_ball(ball.kind, pos.x, pos.y);
+        }
+        Ok(pool_table)
+    }
+}
+
+mod profile_name {
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::Serializer;
+
+    use super::PhysicsProfile;
+
+    pub fn serialize<S>(profile: &PhysicsProfile, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(profile.name)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<PhysicsProfile, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        PhysicsProfile::by_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown profile {}", name)))
+    }
+}
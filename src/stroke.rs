@@ -0,0 +1,99 @@
+use ncollide2d::math::Vector as Vector2;
+
+use super::shot::Shot;
+use super::TIME_STEP;
+
+const BACKSWING_TIME: f32 = 0.3;
+// duration of the forward move, from the softest to the hardest shot
+const SOFT_FORWARD_TIME: f32 = 0.3;
+const HARD_FORWARD_TIME: f32 = 0.06;
+const FOLLOW_THROUGH_TIME: f32 = 0.2;
+
+const BACKSWING_GAP: f32 = 300.;
+const FOLLOW_THROUGH_GAP: f32 = -400.;
+
+/// Distance between the cue tip and the ball while aiming.
+pub fn aim_gap(force: f32) -> f32 {
+    force * 2.5
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Phase {
+    BackSwing,
+    Forward,
+    FollowThrough,
+    Done,
+}
+
+/// The animated move of the cue, the shot is played when the tip
+/// reaches the ball.
+pub struct Stroke {
+    phase: Phase,
+    elapsed: f32,
+    // the force given by the power meter, between 0 and 1
+    power: f32,
+    pub shot: Shot,
+    // position of the white ball when the stroke started
+    pub anchor: Vector2<f32>,
+}
+
+impl Stroke {
+    pub fn new(anchor: Vector2<f32>, shot: Shot, max_force: f32) -> Self {
+        Stroke {
+            phase: Phase::BackSwing,
+            elapsed: 0.,
+            power: (shot.force / max_force).max(0.).min(1.),
+            shot,
+            anchor,
+        }
+    }
+
+    fn phase_duration(&self) -> f32 {
+        match self.phase {
+            Phase::BackSwing => BACKSWING_TIME,
+            Phase::Forward => SOFT_FORWARD_TIME + (HARD_FORWARD_TIME - SOFT_FORWARD_TIME) * self.power,
+            Phase::FollowThrough => FOLLOW_THROUGH_TIME,
+            Phase::Done => 0.,
+        }
+    }
+
+    /// Advance the animation of one step, return true when the tip
+    /// reaches the ball.
+    pub fn update(&mut self) -> bool {
+        if self.phase == Phase::Done {
+            return false;
+        }
+        self.elapsed += TIME_STEP;
+        if self.elapsed < self.phase_duration() {
+            return false;
+        }
+        self.elapsed = 0.;
+        let hit = self.phase == Phase::Forward;
+        self.phase = match self.phase {
+            Phase::BackSwing => Phase::Forward,
+            Phase::Forward => Phase::FollowThrough,
+            Phase::FollowThrough | Phase::Done => Phase::Done,
+        };
+        hit
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.phase == Phase::Done
+    }
+
+    /// Distance between the cue tip and the anchor ball surface,
+    /// negative after the hit.
+    pub fn tip_gap(&self) -> f32 {
+        let progress = (self.elapsed / self.phase_duration()).min(1.);
+        let aim = aim_gap(self.shot.force);
+        let back = aim + BACKSWING_GAP;
+        match self.phase {
+            // slow down at the end of the back swing
+            Phase::BackSwing => aim + (back - aim) * (1. - (1. - progress).powi(2)),
+            // accelerate toward the ball
+            Phase::Forward => back * (1. - progress * progress),
+            Phase::FollowThrough => FOLLOW_THROUGH_GAP * (1. - (1. - progress).powi(2)),
+            Phase::Done => FOLLOW_THROUGH_GAP,
+        }
+    }
+}
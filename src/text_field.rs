@@ -0,0 +1,38 @@
+//! Texts to share: shown in a text field of the browser to be copied, or
+//! pasted in it by the player.
+//!
+//! The native window has no text field, the texts are only logged there
+//! and nothing can be pasted: the game loop must not wait for a terminal.
+
+/// Whether the player can paste a text.
+pub const CAN_ASK: bool = cfg!(target_arch = "wasm32");
+
+/// Show a text, selected, ready to be copied.
+#[cfg(target_arch = "wasm32")]
+pub fn show(title: &str, text: &str) {
+    stdweb::js! {
+        prompt(@{title}, @{text});
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn show(title: &str, text: &str) {
+    info!("{} {}", title, text);
+}
+
+/// Ask for a text, none when the player cancels.
+#[cfg(target_arch = "wasm32")]
+pub fn ask(title: &str) -> Option<String> {
+    let text = stdweb::js! {
+        return prompt(@{title}, "");
+    };
+    match text {
+        stdweb::Value::String(text) => Some(text),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn ask(_title: &str) -> Option<String> {
+    None
+}
@@ -0,0 +1,167 @@
+//! Throw: the friction between two balls at their contact deflects the
+//! object ball from the line of centers, and spins both balls.
+
+use ncollide2d::math::Vector as Vector2;
+
+// the sliding speed taken by a unit of tangential impulse, per unit of
+// inverse mass: each solid ball adds 1 + 5/2 to the surface speed
+const SLIP_PER_IMPULSE: f32 = 3.5;
+// surface speed of the side spin gained per unit of tangential impulse,
+// per unit of inverse mass
+const SPIN_GAIN: f32 = 2.5;
+// the friction of the balls falls with the sliding speed, to a tenth when
+// they slide fast, and to about a third at this speed (1 m/s)
+const FAST_FRICTION: f32 = 0.1;
+const SLIDING_SPEED: f32 = 8400.;
+
+/// What the friction does to a collision.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Throw {
+    // tangential impulse given to the second ball, taken from the first one
+    pub impulse: Vector2<f32>,
+    // surface speeds of the side spins given to the first and the second
+    // ball, positive for a right spin as in Shot::spin
+    pub sides: [f32; 2],
+}
+
+/// The throw of a collision between two balls.
+///
+/// `velocity` is the velocity of the first ball relative to the second
+/// before the hit, `normal` the unit vector from the first ball to the
+/// second one, `side` the surface speed of the side spin of the first
+/// ball and `masses` the masses of both balls. The surfaces slide on each
+/// other because of the cut angle and of the spin, the friction drags the
+/// second ball along, up to the point where they roll on each other. Slow
+/// shots throw the most.
+pub fn throw(
+    velocity: Vector2<f32>,
+    normal: Vector2<f32>,
+    side: f32,
+    friction: f32,
+    masses: [f32; 2],
+) -> Option<Throw> {
+    let normal_speed = velocity.dot(&normal);
+    if normal_speed <= 0. || friction <= 0. {
+        return None;
+    }
+    // left of the line of centers, a right spin moves the front of the
+    // ball to the left
+    let left = Vector2::new(normal.y, -normal.x);
+    let sliding = velocity - normal * normal_speed + left * side;
+    let sliding_speed = sliding.norm();
+    if sliding_speed < 1e-3 {
+        return None;
+    }
+    let inverse_masses = [1. / masses[0], 1. / masses[1]];
+    // the impulse along the line of centers of an elastic hit
+    let normal_impulse = 2. * normal_speed / (inverse_masses[0] + inverse_masses[1]);
+    let no_slip = SLIP_PER_IMPULSE * (inverse_masses[0] + inverse_masses[1]);
+
+    let slow = (-sliding_speed / SLIDING_SPEED).exp();
+    let friction = friction * (FAST_FRICTION + (1. - FAST_FRICTION) * slow);
+    let impulse = (friction * normal_impulse).min(sliding_speed / no_slip);
+    let direction = sliding / sliding_speed;
+    // dragged to the left at its back, the second ball turns to the right,
+    // and so does the first one dragged to the right at its front
+    let spin = -direction.dot(&left) * impulse * SPIN_GAIN;
+    Some(Throw {
+        impulse: direction * impulse,
+        sides: [spin * inverse_masses[0], spin * inverse_masses[1]],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EQUAL: [f32; 2] = [1., 1.];
+    const FRICTION: f32 = 0.1;
+    const SPEED: f32 = 3000.;
+
+    // the cue ball goes to the right, y down as on the screen: the object
+    // ball is on the left of its path when the cut angle is positive
+    fn cut(degrees: f32, side: f32, masses: [f32; 2]) -> Option<Throw> {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let normal = Vector2::new(cos, -sin);
+        throw(Vector2::new(SPEED, 0.), normal, side, FRICTION, masses)
+    }
+
+    #[test]
+    fn full_hit_without_spin_does_not_throw() {
+        assert_eq!(cut(0., 0., EQUAL), None);
+    }
+
+    #[test]
+    fn cut_throws_along_the_cue_ball() {
+        for &degrees in [-30f32, -10., 10., 30.].iter() {
+            let throw = cut(degrees, 0., EQUAL).unwrap();
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            let normal = Vector2::new(cos, -sin);
+            assert!(throw.impulse.dot(&normal).abs() < 1e-2);
+            // forward, along the path of the cue ball
+            assert!(throw.impulse.x > 0., "{} degrees: {:?}", degrees, throw);
+            assert_eq!(throw.impulse.y.signum(), degrees.signum());
+        }
+    }
+
+    #[test]
+    fn cut_spin_turns_with_the_side_of_the_cut() {
+        let left_cut = cut(30., 0., EQUAL).unwrap();
+        let right_cut = cut(-30., 0., EQUAL).unwrap();
+        assert!(left_cut.sides[1] > 0.);
+        assert!(right_cut.sides[1] < 0.);
+        assert_eq!(left_cut.sides[0], left_cut.sides[1]);
+    }
+
+    #[test]
+    fn side_spin_throws_a_full_hit_the_other_way() {
+        let right_spin = cut(0., SPEED * 0.2, EQUAL).unwrap();
+        let left_spin = cut(0., -SPEED * 0.2, EQUAL).unwrap();
+        // a right spin throws to the left, to negative y
+        assert!(right_spin.impulse.y < 0.);
+        assert!(left_spin.impulse.y > 0.);
+        // the gear effect turns the object ball the other way
+        assert!(right_spin.sides[1] < 0.);
+        assert!(left_spin.sides[1] > 0.);
+    }
+
+    #[test]
+    fn outside_spin_cancels_the_throw_of_a_cut() {
+        let plain = cut(30., 0., EQUAL).unwrap();
+        let sliding = SPEED * 30f32.to_radians().sin();
+        let rolling = cut(30., sliding, EQUAL).map_or(0., |throw| throw.impulse.norm());
+        assert!(rolling < 1., "{}", rolling);
+        let outside = cut(30., sliding * 0.5, EQUAL).unwrap();
+        assert!(outside.impulse.norm() < plain.impulse.norm());
+    }
+
+    #[test]
+    fn slow_shots_throw_more() {
+        let throw_angle = |speed: f32| {
+            let normal = Vector2::new(30f32.to_radians().cos(), -30f32.to_radians().sin());
+            let thrown = throw(Vector2::new(speed, 0.), normal, 0., FRICTION, EQUAL).unwrap();
+            thrown.impulse.norm() / speed
+        };
+        assert!(throw_angle(1000.) > throw_angle(20000.));
+    }
+
+    // the friction stops at the point where the surfaces roll on each other
+    #[test]
+    fn the_sliding_never_reverses() {
+        for &masses in [EQUAL, [1.25, 1.], [1., 1.25]].iter() {
+            let velocity = Vector2::new(SPEED, 0.);
+            let normal = Vector2::new(0.8, -0.6);
+            let thrown = throw(velocity, normal, 0., 1., masses).unwrap();
+            let sliding = velocity - normal * velocity.dot(&normal);
+            let taken = thrown.impulse * SLIP_PER_IMPULSE * (1. / masses[0] + 1. / masses[1]);
+            assert!((sliding - taken).norm() < 1., "{:?}", masses);
+        }
+    }
+
+    #[test]
+    fn the_lighter_ball_spins_more() {
+        let throw = cut(30., 0., [1.25, 1.]).unwrap();
+        assert!(throw.sides[0] < throw.sides[1]);
+        assert!(throw.sides[0] > 0.);
+    }
+}
@@ -0,0 +1,170 @@
+//! Balls falling in the holes, and the rail of the pocketed balls.
+
+use nalgebra::UnitQuaternion;
+
+use quicksilver::{
+    geom::{Rectangle, Transform, Vector},
+    graphics::{
+        Background::{Blended, Col, Img},
+        Color, Font, FontStyle,
+    },
+    lifecycle::{Asset, Window},
+    Result,
+};
+
+use super::ball_sprites::BallSprites;
+use super::camera::Camera;
+use super::game::Game;
+use super::{PocketedBall, PoolTable, TIME_STEP};
+
+const FALL_DURATION: f32 = 0.35;
+// size of the ball at the end of the fall
+const FALL_SCALE: f32 = 0.6;
+
+const TRAY_BALL_SIZE: f32 = 24.;
+const TRAY_MARGIN: f32 = 10.;
+const TRAY_FONT_SIZE: f32 = 18.;
+const SPRITE_SIZE: u32 = 32;
+const TRAY_BALLS_PER_ROW: usize = 8;
+
+struct Fall {
+    ball: PocketedBall,
+    orientation: UnitQuaternion<f32>,
+    elapsed: f32,
+}
+
+pub struct Tray {
+    falls: Vec<Fall>,
+    sprites: BallSprites,
+}
+
+impl Tray {
+    pub fn new() -> Self {
+        Tray {
+            falls: Vec::new(),
+            sprites: BallSprites::new(SPRITE_SIZE),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.falls.clear();
+    }
+
+    /// Start the fall of a ball in its hole.
+    pub fn ball_pocketed(&mut self, ball: PocketedBall, orientation: UnitQuaternion<f32>) {
+        if ball.hole.is_none() {
+            return;
+        }
+        self.falls.push(Fall {
+            ball,
+            orientation,
+            elapsed: 0.,
+        });
+    }
+
+    pub fn update(&mut self) {
+        for fall in self.falls.iter_mut() {
+            fall.elapsed += TIME_STEP;
+        }
+        self.falls.retain(|fall| fall.elapsed < FALL_DURATION);
+    }
+
+    pub fn draw_falls(
+        &mut self,
+        window: &mut Window,
+        pool_table: &PoolTable,
+        camera: &Camera,
+    ) -> Result<()> {
+        let radius = pool_table.profile.ball_radius;
+        for fall in self.falls.iter() {
+            let hole = pool_table.holes[fall.ball.hole.unwrap()];
+            let hole_pos = pool_table.world.body_part(hole).position().translation.vector;
+
+            let progress = fall.elapsed / FALL_DURATION;
+            // accelerate like a falling ball
+            let ease = progress * progress;
+            let pos = fall.ball.position + (hole_pos - fall.ball.position) * ease;
+            let size = camera.scale(radius * 2.) * (1. - (1. - FALL_SCALE) * ease);
+            let light = 1. - 0.7 * ease;
+
+            let image = self.sprites.get(fall.ball.kind, &fall.orientation)?;
+            window.draw_ex(
+                &Rectangle::new_sized((size, size)).with_center(camera.to_screen(pos)),
+                Blended(image, Color::WHITE.with_red(light).with_green(light).with_blue(light)),
+                Transform::rotate(camera.rotation()),
+                0,
+            );
+        }
+        Ok(())
+    }
+
+    /// Draw the pocketed balls of every player, in the corners of the screen.
+    pub fn draw(&mut self, window: &mut Window, game: &Game, font: &mut Asset<Font>) -> Result<()> {
+        let screen = window.screen_size();
+
+        for player in 0..game.players() {
+            let balls = game.tray(player);
+            let width = TRAY_BALL_SIZE * (TRAY_BALLS_PER_ROW as f32 + 0.5);
+            let left = if player == 0 {
+                TRAY_MARGIN
+            } else {
+                screen.x - TRAY_MARGIN - width
+            };
+            let top = TRAY_MARGIN + TRAY_FONT_SIZE + 4.;
+
+            let rail = if player == game.current_player {
+                Color::BLACK.with_alpha(0.7)
+            } else {
+                Color::BLACK.with_alpha(0.35)
+            };
+            // all the object balls fit on two rows.
+            window.draw(
+                &Rectangle::new((left, top), (width, TRAY_BALL_SIZE * 2. + 4.)),
+                Col(rail),
+            );
+
+            for (idx, ball) in balls.iter().enumerate() {
+                let (row, col) = (idx / TRAY_BALLS_PER_ROW, idx % TRAY_BALLS_PER_ROW);
+                let center = Vector::new(
+                    left + TRAY_BALL_SIZE * (col as f32 + 0.75),
+                    top + 2. + TRAY_BALL_SIZE * (row as f32 + 0.5),
+                );
+                let image = self.sprites.get(ball.kind, &UnitQuaternion::identity())?;
+                window.draw(
+                    &Rectangle::new_sized((TRAY_BALL_SIZE, TRAY_BALL_SIZE)).with_center(center),
+                    Img(image),
+                );
+            }
+
+            let mut label = if player == game.current_player {
+                format!("> Player {}", player + 1)
+            } else {
+                format!("Player {}", player + 1)
+            };
+            if let Some(group) = game.group(player) {
+                label = format!("{} ({})", label, group.name());
+            }
+            if game.winner() == Some(player) {
+                label = format!("{} wins", label);
+            }
+            font.execute(|font| {
+                let image = font.render(&label, &FontStyle::new(TRAY_FONT_SIZE, Color::WHITE))?;
+                let area = Rectangle::new((left, TRAY_MARGIN), image.area().size);
+                window.draw(&area, Img(&image));
+                Ok(())
+            })?;
+        }
+
+        if game.winner().is_some() {
+            font.execute(|font| {
+                let style = FontStyle::new(TRAY_FONT_SIZE, Color::WHITE);
+                let image = font.render("Game over, shoot for a new game", &style)?;
+                let area = Rectangle::new_sized(image.area().size)
+                    .with_center((screen.x * 0.5, TRAY_MARGIN + TRAY_FONT_SIZE * 0.5));
+                window.draw(&area, Img(&image));
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+}
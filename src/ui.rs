@@ -0,0 +1,859 @@
+//! The game in a window: the table, the cue, the menus and the input.
+
+use std::collections::HashMap;
+
+use nalgebra as na;
+
+use ncollide2d::{
+    math::Vector as Vector2,
+    shape::{Ball, Cuboid},
+};
+
+use nphysics2d::object::BodyHandle;
+
+use quicksilver::{
+    geom::{Circle, Rectangle, Transform, Vector},
+    graphics::{
+        Background::{Blended, Col, Img},
+        Color, Font, Image, PixelFormat, View,
+    },
+    input::MouseButton,
+    lifecycle::{self, Asset, Settings, State, Window},
+    Result,
+};
+
+use super::ball_sprites::BallSprites;
+use super::camera::{Camera, Pinch};
+use super::debug::DebugOverlay;
+use super::drill::{load_drills, Drill, DrillSession, Records};
+use super::editor::{Editor, EditorAction};
+use super::game::{Game, OffTableRule};
+use super::gamepad::GamepadState;
+use super::input::Action;
+use super::link::{self, ShotLink};
+use super::menu::{GameSettings, MenuAction, PauseMenu, FONT_FILE};
+use super::notation::Position;
+use super::shot::{clamp_elevation, clamp_spin, Shot};
+use super::sound::SoundBank;
+use super::sprite;
+use super::state::TableState;
+use super::stroke::{aim_gap, Stroke};
+use super::tray::Tray;
+use super::{
+    BallKind, GameMode, PoolTable, TableEvent, BAND, BORDER, HEIGHT, HOLE_SIZE, MARGIN_LEFT,
+    MARGIN_TOP, MAX_FORCE, TIME_STEP, WIDTH,
+};
+
+const CANE_SIZE: f32 = 1800.;
+const CANE_WIDTH: f32 = 40.;
+const HELP_LINE_SIZE: f32 = 2600.;
+const HELP_LINE_WIDTH: f32 = 3.;
+const FORCE_STEP: f32 = 50.;
+const ANGLE_STEP: f32 = 0.5;
+
+const ZOOM_STEP: f32 = 1.2;
+
+const POWER_BAR_WIDTH: f32 = 16.;
+const POWER_BAR_HEIGHT: f32 = 200.;
+const POWER_BAR_MARGIN: f32 = 10.;
+// radius of the cue ball drawn next to the power bar
+const SPIN_INDICATOR_SIZE: f32 = 30.;
+const SPIN_DOT_SIZE: f32 = 5.;
+const SPIN_STEP: f32 = 0.03;
+// the cue seen from the side, left of the spin indicator
+const ELEVATION_CUE_SIZE: f32 = 50.;
+const ELEVATION_CUE_WIDTH: f32 = 4.;
+const ELEVATION_STEP: f32 = 0.5;
+// distance of the camera above the table, relative to the radius of the
+// balls, the balls in the air look bigger
+const CAMERA_HEIGHT: f32 = 20.;
+
+// size, in pixels, of the rendered ball sprites
+const BALL_SPRITE_SIZE: u32 = 48;
+const CLOTH_TEXTURE_SIZE: (u32, u32) = (256, 128);
+const WOOD_TEXTURE_SIZE: (u32, u32) = (256, 160);
+
+const WINDOW_WIDTH: f32 = 1024.;
+const WINDOW_HEIGHT: f32 = 768.;
+
+struct PoolGameUI {
+    pool_table: PoolTable,
+    game: Game,
+    tray: Tray,
+    stroke: Option<Stroke>,
+    cane_rotation: f32,
+    cane_force: f32,
+    // where the cue hits the cue ball, see Shot
+    spin: Vector2<f32>,
+    elevation: f32,
+    // positions before every shot of the game, for undo
+    history: Vec<(TableState, Game)>,
+    drills: Asset<Vec<Drill>>,
+    drill: Option<DrillSession>,
+    records: Records,
+    editor: Option<Editor>,
+    // balls that left the table, put back when the balls are at rest
+    respots: Vec<BallKind>,
+
+    settings: GameSettings,
+    menu: PauseMenu,
+    paused: bool,
+    font: Asset<Font>,
+    debug_overlay: DebugOverlay,
+    camera: Camera,
+    pinch: Pinch,
+    // last mouse position while dragging the view
+    drag: Option<Vector>,
+
+    // rotation of every ball, updated from its rolling
+    orientations: HashMap<BodyHandle, na::UnitQuaternion<f32>>,
+    ball_sprites: BallSprites,
+    cloth: Image,
+    wood: Image,
+    sounds: SoundBank,
+}
+
+impl State for PoolGameUI {
+    fn new() -> Result<PoolGameUI> {
+        let settings = GameSettings::new();
+        // the position of a shot link, or the rack of a new game
+        let link = link::read();
+        let position = link.as_ref().and_then(|link| match link.position.build() {
+            Ok(position) => Some(position),
+            Err(err) => {
+                warn!("Ignore the position of the link: {}", err);
+                None
+            }
+        });
+        // a shot without its position would be played on the rack
+        let shot = link
+            .as_ref()
+            .filter(|_| position.is_some())
+            .and_then(|link| link.shot);
+        let (pool_table, game) = position.unwrap_or_else(|| {
+            let mut pool_table = PoolTable::with_profile(settings.profile());
+            pool_table.initialze_world(settings.mode);
+            (pool_table, Game::new(settings.mode))
+        });
+
+        let cane_rotation = 0.;
+        let cane_force = 5.;
+
+        let mut pool_game = PoolGameUI {
+            pool_table,
+            game,
+            tray: Tray::new(),
+            stroke: None,
+            cane_rotation,
+            cane_force,
+            spin: Vector2::new(0., 0.),
+            elevation: 0.,
+            history: Vec::new(),
+            drills: Asset::new(load_drills()),
+            drill: None,
+            records: Records::load(),
+            editor: None,
+            respots: Vec::new(),
+            settings,
+            menu: PauseMenu::new(),
+            paused: false,
+            font: Asset::new(Font::load(FONT_FILE)),
+            debug_overlay: DebugOverlay::new(),
+            camera: Camera::new(Vector::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+            pinch: Pinch::new(),
+            drag: None,
+            orientations: HashMap::new(),
+            ball_sprites: BallSprites::new(BALL_SPRITE_SIZE),
+            cloth: texture(sprite::cloth, CLOTH_TEXTURE_SIZE)?,
+            wood: texture(sprite::wood, WOOD_TEXTURE_SIZE)?,
+            sounds: SoundBank::new(),
+        };
+        if let Some(shot) = shot {
+            pool_game.cane_rotation = shot.angle;
+            pool_game.cane_force = shot.force.min(MAX_FORCE);
+            pool_game.spin = shot.spin;
+            pool_game.elevation = shot.elevation;
+            if link.map_or(false, |link| link.replay) {
+                pool_game.start_stroke();
+            }
+        }
+        Ok(pool_game)
+    }
+
+    fn draw(&mut self, window: &mut Window) -> Result<()> {
+        self.draw_table(window)?;
+
+        for hole in self.pool_table.holes.iter() {
+            self.draw_hole(window, hole);
+        }
+
+        self.tray.draw_falls(window, &self.pool_table, &self.camera)?;
+        for ball in self.pool_table.balls() {
+            self.draw_ball_shadow(window, &ball);
+        }
+        for ball in self.pool_table.balls() {
+            self.draw_ball(window, &ball)?;
+        }
+
+        if let Some(ref editor) = self.editor {
+            editor.draw(window, &self.pool_table, &self.camera, &mut self.font)?;
+        } else if let Some(ref stroke) = self.stroke {
+            self.draw_cue(window, stroke.anchor, stroke.shot.angle, stroke.tip_gap());
+            self.draw_power_bar(window, stroke.shot.force);
+            self.draw_spin(window, stroke.shot.spin, stroke.shot.force);
+            self.draw_elevation(window, stroke.shot.elevation);
+        } else if !self.pool_table.has_force() && self.game.winner().is_none() {
+            if self.pool_table.white_ball_handle.is_none() {
+                self.pool_table.respawn_white_ball();
+            }
+            let ball_object = self
+                .pool_table
+                .world
+                .body_part(self.pool_table.white_ball_handle.unwrap());
+            let ball_pos = ball_object.position().translation.vector;
+
+            self.draw_cue(window, ball_pos, self.cane_rotation, aim_gap(self.cane_force));
+            self.draw_power_bar(window, self.cane_force);
+            self.draw_spin(window, self.spin, self.cane_force);
+            self.draw_elevation(window, self.elevation);
+
+            let queue = Cuboid::new(Vector2::new(
+                self.camera.scale(HELP_LINE_SIZE),
+                self.camera.scale(HELP_LINE_WIDTH),
+            ));
+            let mut pos = ball_pos;
+
+            let rot = self.cane_rotation.to_radians();
+            let radius = self.pool_table.profile.ball_radius;
+            pos.x = pos.x + (HELP_LINE_SIZE + radius + (self.cane_force * 5.)) * rot.cos();
+            pos.y = pos.y + (HELP_LINE_SIZE + radius + (self.cane_force * 5.)) * rot.sin();
+            if self.settings.aim_assist {
+                window.draw_ex(
+                    &Rectangle::from_cuboid(self.camera.to_screen(pos), &queue),
+                    Col(Color::BLUE),
+                    Transform::rotate(self.cane_rotation + self.camera.rotation()),
+                    0, // we don't really care about the Z value
+                );
+            }
+        }
+
+        self.tray.draw(window, &self.game, &mut self.font)?;
+        if let Some(ref drill) = self.drill {
+            drill.draw(window, &mut self.font, &self.records)?;
+        }
+
+        self.debug_overlay.record_frame(window);
+        if self.settings.debug_overlay {
+            self.debug_overlay.draw(window, &self.pool_table, &self.camera);
+        }
+
+        if self.paused {
+            self.menu.draw(window, &mut self.font, &self.settings)?;
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, window: &mut Window) -> Result<()> {
+        self.resize(window);
+        let gamepad = GamepadState::read(window);
+
+        let bindings = &self.settings.bindings;
+        if (bindings.is_pressed(window, Action::Pause) || gamepad.menu)
+            && !self.menu.is_capturing()
+        {
+            self.paused = !self.paused;
+        }
+
+        if self.paused {
+            if !self.menu.has_drills() {
+                let menu = &mut self.menu;
+                self.drills.execute(|drills| {
+                    menu.set_drills(drills.iter().map(|drill| drill.name.clone()).collect());
+                    Ok(())
+                })?;
+            }
+            match self.menu.update(window, &gamepad, &mut self.settings) {
+                Some(MenuAction::Resume) => self.paused = false,
+                Some(MenuAction::NewGame) => {
+                    self.new_game();
+                    self.paused = false;
+                }
+                Some(MenuAction::EditTable) => {
+                    self.edit_table();
+                    self.paused = false;
+                }
+                Some(MenuAction::StartDrill(idx)) => {
+                    let mut drill = None;
+                    self.drills.execute(|drills| {
+                        drill = drills.get(idx).cloned();
+                        Ok(())
+                    })?;
+                    if let Some(drill) = drill {
+                        self.start_drill(drill);
+                        self.paused = false;
+                    }
+                }
+                None => {}
+            }
+            return Ok(());
+        }
+
+        if self.editor.is_some() {
+            self.update_editor(window);
+            return Ok(());
+        }
+
+        self.pool_table.step();
+        let events = self.pool_table.take_events();
+        for cue in self.settings.mixer.cues(&events) {
+            self.sounds.play(&cue)?;
+        }
+        for event in events.iter() {
+            match *event {
+                TableEvent::Pocketed { ball, .. } => self.ball_pocketed(ball),
+                TableEvent::OffTable { ball, .. } => self.ball_off_table(ball),
+                _ => {}
+            }
+        }
+        if let Some(summary) = self.pool_table.take_shot_complete() {
+            info!(
+                "Shot complete in {} steps, first hit {:?}, {} balls pocketed",
+                summary.steps,
+                summary.first_hit,
+                summary.pocketed.len()
+            );
+            for kind in self.respots.drain(..) {
+                if self.pool_table.respot_ball(kind).is_none() {
+                    warn!("No room to respot the {:?} ball", kind);
+                }
+            }
+            self.game.shot_complete(&summary);
+            if let Some(ref mut drill) = self.drill {
+                drill.shot_complete(&self.pool_table, &mut self.records);
+            }
+            // every attempt starts from the layout of the drill
+            if self.drill.is_some() {
+                self.restart_drill();
+            }
+        }
+        self.tray.update();
+        self.roll_balls();
+        self.update_camera(window);
+
+        if self.stroke.is_some() {
+            self.update_stroke();
+            return Ok(());
+        }
+
+        if self.settings.bindings.is_pressed(window, Action::Undo)
+            && !self.pool_table.has_force()
+        {
+            self.undo();
+            return Ok(());
+        }
+
+        let bindings = &self.settings.bindings;
+        let mut force = FORCE_STEP;
+        let mut angle = ANGLE_STEP;
+
+        if bindings.is_down(window, Action::Coarse) {
+            force = force * self.settings.coarse_multiplier;
+            angle = angle * self.settings.coarse_multiplier;
+        } else if bindings.is_down(window, Action::Fine) {
+            force = force * self.settings.fine_multiplier;
+            angle = angle * self.settings.fine_multiplier;
+        }
+
+        self.cane_rotation += ANGLE_STEP
+            * (gamepad.coarse_rotation * self.settings.coarse_multiplier
+                + gamepad.fine_rotation * self.settings.fine_multiplier);
+        self.cane_force += FORCE_STEP * gamepad.power;
+
+        if bindings.is_down(window, Action::AimRight) {
+            self.cane_rotation += angle;
+        }
+        if bindings.is_down(window, Action::AimLeft) {
+            self.cane_rotation -= angle;
+        }
+        if bindings.is_down(window, Action::PowerUp) {
+            self.cane_force += force;
+        }
+        if bindings.is_down(window, Action::PowerDown) {
+            self.cane_force -= force;
+        }
+        let mut spin = Vector2::new(0., 0.);
+        if bindings.is_down(window, Action::SpinUp) {
+            spin.y += SPIN_STEP;
+        }
+        if bindings.is_down(window, Action::SpinDown) {
+            spin.y -= SPIN_STEP;
+        }
+        if bindings.is_down(window, Action::SpinLeft) {
+            spin.x -= SPIN_STEP;
+        }
+        if bindings.is_down(window, Action::SpinRight) {
+            spin.x += SPIN_STEP;
+        }
+        self.spin = clamp_spin(self.spin + spin);
+        if bindings.is_down(window, Action::RaiseCue) {
+            self.elevation += ELEVATION_STEP;
+        }
+        if bindings.is_down(window, Action::LowerCue) {
+            self.elevation -= ELEVATION_STEP;
+        }
+        self.elevation = clamp_elevation(self.elevation);
+
+        if self.cane_force < 0. {
+            self.cane_force = 0.;
+        }
+        if self.cane_force > MAX_FORCE {
+            self.cane_force = MAX_FORCE;
+        }
+
+        // one stroke per key press, holding the key does not shoot again,
+        // once the game is over it starts a new one
+        if (self.settings.bindings.is_pressed(window, Action::Shoot) || gamepad.shoot)
+            && self.stroke.is_none()
+            && !self.pool_table.has_force()
+        {
+            if self.game.winner().is_some() {
+                self.new_game();
+            } else {
+                self.start_stroke();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl PoolGameUI {
+    /// Start the move of the cue for the current aim, the link of the
+    /// shot is put in the URL.
+    fn start_stroke(&mut self) {
+        let ball = match self.pool_table.white_ball_handle {
+            Some(ball) => ball,
+            None => return,
+        };
+        let shot = Shot {
+            angle: self.cane_rotation,
+            force: self.cane_force,
+            spin: self.spin,
+            elevation: self.elevation,
+        };
+        let position = Position::of(&self.pool_table, &self.game);
+        info!("Position: {}", position);
+        link::write(&ShotLink {
+            position,
+            shot: Some(shot),
+            replay: true,
+        });
+
+        self.history.push((TableState::of(&self.pool_table), self.game.clone()));
+        let anchor = self.pool_table.world.body_part(ball).position().translation.vector;
+        self.stroke = Some(Stroke::new(anchor, shot, MAX_FORCE));
+    }
+
+    fn resize(&mut self, window: &mut Window) {
+        let size = screen_size(window);
+        if size != self.camera.screen_size() {
+            window.set_size(size);
+            window.set_view(View::new(Rectangle::new_sized(size)));
+            self.camera.resize(size);
+        }
+    }
+
+    fn update_camera(&mut self, window: &mut Window) {
+        let mouse = window.mouse();
+
+        let wheel = mouse.wheel().y;
+        if wheel != 0. {
+            // scrolling up zooms in
+            self.camera.zoom_at(ZOOM_STEP.powf(-wheel.signum()), mouse.pos());
+        }
+
+        let pinch = self.pinch.take();
+        if pinch != 1. {
+            let center = self.camera.screen_size() * 0.5;
+            self.camera.zoom_at(pinch, center);
+        }
+
+        if mouse[MouseButton::Left].is_down() {
+            if let Some(last) = self.drag {
+                self.camera.pan(mouse.pos() - last);
+            }
+            self.drag = Some(mouse.pos());
+        } else {
+            self.drag = None;
+        }
+
+        if self.pool_table.has_force() {
+            if let Some(center) = self.pool_table.moving_balls_center() {
+                self.camera.follow(center);
+            }
+        }
+        self.camera.update();
+    }
+
+    fn new_game(&mut self) {
+        info!("New {} game", self.settings.mode.name());
+        self.drill = None;
+        self.editor = None;
+        let mut pool_table = PoolTable::with_profile(self.settings.profile());
+        pool_table.initialze_world(self.settings.mode);
+        self.pool_table = pool_table;
+        self.game = Game::new(self.settings.mode);
+        self.tray.clear();
+        self.respots.clear();
+        self.stroke = None;
+        self.orientations.clear();
+        self.history.clear();
+        self.cane_rotation = 0.;
+        self.cane_force = 5.;
+    }
+
+    fn start_drill(&mut self, drill: Drill) {
+        info!("Start drill {}", drill.name);
+        self.drill = Some(DrillSession::new(drill));
+        self.editor = None;
+        self.history.clear();
+        self.cane_rotation = 0.;
+        self.cane_force = 5.;
+        self.restart_drill();
+    }
+
+    fn restart_drill(&mut self) {
+        if let Some(ref session) = self.drill {
+            self.pool_table = session.drill.table.build_with(self.settings.profile());
+            self.game = Game::new(GameMode::Practice);
+            self.tray.clear();
+            self.respots.clear();
+            self.stroke = None;
+            self.orientations.clear();
+        }
+    }
+
+    /// Edit the current position, the balls are stopped where they are.
+    fn edit_table(&mut self) {
+        self.pool_table = TableState::of(&self.pool_table).build();
+        self.editor = Some(Editor::new());
+        self.drill = None;
+        self.stroke = None;
+        self.tray.clear();
+        self.respots.clear();
+        self.orientations.clear();
+    }
+
+    fn update_editor(&mut self, window: &mut Window) {
+        let (action, dragging) = match self.editor {
+            Some(ref mut editor) => (
+                editor.update(window, &mut self.pool_table, &self.camera),
+                editor.is_dragging(),
+            ),
+            None => return,
+        };
+        if !dragging {
+            self.update_camera(window);
+        }
+        if action == Some(EditorAction::Play) {
+            // start from a fresh world, without the events of the edition
+            self.pool_table = TableState::of(&self.pool_table).build();
+            self.editor = None;
+            self.game = Game::new(self.pool_table.mode);
+            self.history.clear();
+            self.cane_rotation = 0.;
+            self.cane_force = 5.;
+        }
+    }
+
+    /// Put the balls back where they were before the last shot.
+    fn undo(&mut self) {
+        if let Some((state, game)) = self.history.pop() {
+            self.pool_table = state.build();
+            self.game = game;
+            self.tray.clear();
+            self.respots.clear();
+            self.orientations.clear();
+        }
+    }
+
+    fn update_stroke(&mut self) {
+        let mut stroke = match self.stroke.take() {
+            Some(stroke) => stroke,
+            None => return,
+        };
+        if stroke.update() {
+            self.pool_table.shoot(&stroke.shot);
+            self.cane_force = FORCE_STEP;
+            self.spin = Vector2::new(0., 0.);
+            self.elevation = 0.;
+        }
+        if !stroke.is_done() {
+            self.stroke = Some(stroke);
+        }
+    }
+
+    fn draw_cue(&self, window: &mut Window, ball_pos: Vector2<f32>, rotation: f32, gap: f32) {
+        let queue = Cuboid::new(Vector2::new(
+            self.camera.scale(CANE_SIZE),
+            self.camera.scale(CANE_WIDTH),
+        ));
+        let mut pos = ball_pos;
+
+        let rot = rotation.to_radians();
+        let radius = self.pool_table.profile.ball_radius;
+        pos.x = pos.x - (CANE_SIZE + radius + gap) * rot.cos();
+        pos.y = pos.y - (CANE_SIZE + radius + gap) * rot.sin();
+        window.draw_ex(
+            &Rectangle::from_cuboid(self.camera.to_screen(pos), &queue),
+            Col(Color::RED),
+            Transform::rotate(rotation + self.camera.rotation()),
+            0, // we don't really care about the Z value
+        );
+    }
+
+    fn draw_power_bar(&self, window: &mut Window, force: f32) {
+        let screen = window.screen_size();
+        let power = (force / MAX_FORCE).max(0.).min(1.);
+        let left = screen.x - POWER_BAR_MARGIN - POWER_BAR_WIDTH;
+        let bottom = screen.y - POWER_BAR_MARGIN;
+
+        window.draw(
+            &Rectangle::new(
+                (left - 2., bottom - POWER_BAR_HEIGHT - 2.),
+                (POWER_BAR_WIDTH + 4., POWER_BAR_HEIGHT + 4.),
+            ),
+            Col(Color::BLACK.with_alpha(0.6)),
+        );
+        // from green to red
+        let color = Color::WHITE
+            .with_red(power.min(0.5) * 2.)
+            .with_green((1. - power).min(0.5) * 2.)
+            .with_blue(0.);
+        let height = POWER_BAR_HEIGHT * power;
+        window.draw(
+            &Rectangle::new((left, bottom - height), (POWER_BAR_WIDTH, height)),
+            Col(color),
+        );
+    }
+
+    /// The face of the cue ball and where the cue hits it, left of the power
+    /// bar. The cue miscues out of the lighter disk, smaller for hard shots.
+    fn draw_spin(&self, window: &mut Window, spin: Vector2<f32>, force: f32) {
+        let screen = window.screen_size();
+        let center = Vector::new(
+            screen.x - POWER_BAR_MARGIN * 2. - POWER_BAR_WIDTH - SPIN_INDICATOR_SIZE,
+            screen.y - POWER_BAR_MARGIN - SPIN_INDICATOR_SIZE,
+        );
+        window.draw(
+            &Circle::new(center, SPIN_INDICATOR_SIZE),
+            Col(Color::WHITE.with_alpha(0.6)),
+        );
+        let max_offset = self.pool_table.profile.miscue.max_offset(force);
+        window.draw(
+            &Circle::new(center, max_offset * (SPIN_INDICATOR_SIZE - SPIN_DOT_SIZE)),
+            Col(Color::WHITE.with_alpha(0.8)),
+        );
+        // the follow is on the top of the ball
+        let tip = center + Vector::new(spin.x, -spin.y) * (SPIN_INDICATOR_SIZE - SPIN_DOT_SIZE);
+        window.draw(&Circle::new(tip, SPIN_DOT_SIZE), Col(Color::RED));
+    }
+
+    /// The cue ball and the cue seen from the side, left of the spin indicator.
+    fn draw_elevation(&self, window: &mut Window, elevation: f32) {
+        let screen = window.screen_size();
+        let center = Vector::new(
+            screen.x - POWER_BAR_MARGIN * 3. - POWER_BAR_WIDTH - SPIN_INDICATOR_SIZE * 2.5,
+            screen.y - POWER_BAR_MARGIN - SPIN_INDICATOR_SIZE * 0.5,
+        );
+        let radius = SPIN_INDICATOR_SIZE * 0.5;
+        window.draw(
+            &Circle::new(center, radius),
+            Col(Color::WHITE.with_alpha(0.8)),
+        );
+        // the cue rises behind the ball, on the left
+        let rot = elevation.to_radians();
+        let distance = radius + ELEVATION_CUE_SIZE * 0.5;
+        let cue = center - Vector::new(rot.cos(), rot.sin()) * distance;
+        window.draw_ex(
+            &Rectangle::new_sized((ELEVATION_CUE_SIZE, ELEVATION_CUE_WIDTH)).with_center(cue),
+            Col(Color::RED),
+            Transform::rotate(elevation),
+            1,
+        );
+    }
+
+    fn ball_pocketed(&mut self, ball: BodyHandle) {
+        if let Some(pocketed) = self.pool_table.pocketed_ball(ball) {
+            let orientation = self
+                .orientations
+                .get(&ball)
+                .cloned()
+                .unwrap_or_else(na::UnitQuaternion::identity);
+            self.game.ball_pocketed(&pocketed);
+            self.tray.ball_pocketed(pocketed, orientation);
+        }
+    }
+
+    fn ball_off_table(&mut self, ball: BodyHandle) {
+        if let Some(off_table) = self.pool_table.pocketed_ball(ball) {
+            info!("The {:?} ball left the table", off_table.kind);
+            if self.game.ball_off_table(&off_table) == OffTableRule::Respot {
+                self.respots.push(off_table.kind);
+            }
+        }
+    }
+
+    fn roll_balls(&mut self) {
+        let radius = self.pool_table.profile.ball_radius;
+        for ball in self.pool_table.balls() {
+            let vel = self.pool_table.world.rigid_body(ball).unwrap().velocity().linear;
+            // rolling without sliding, the z axis goes in the table.
+            let rotation = na::Vector3::new(vel.y, -vel.x, 0.) * (TIME_STEP / radius);
+            let orientation = self
+                .orientations
+                .entry(ball)
+                .or_insert_with(na::UnitQuaternion::identity);
+            *orientation = na::UnitQuaternion::from_scaled_axis(rotation) * *orientation;
+        }
+    }
+
+    fn draw_ball_shadow(&self, window: &mut Window, handle: &BodyHandle) {
+        let ball_object = self.pool_table.world.body_part(handle.clone());
+        let pos = ball_object.position().translation.vector;
+        let radius = self.pool_table.profile.ball_radius;
+        // the shadow of a ball in the air falls further
+        let height = 1. + self.pool_table.height(*handle) / radius;
+        let offset = Vector2::new(radius * 0.12, radius * 0.18) * height;
+        window.draw(
+            &Circle::new(
+                self.camera.to_screen(pos + offset),
+                self.camera.scale(radius),
+            ),
+            Col(Color::BLACK.with_alpha(0.35)),
+        );
+    }
+
+    fn draw_ball(&mut self, window: &mut Window, handle: &BodyHandle) -> Result<()> {
+        let kind = match self.pool_table.ball_kind(*handle) {
+            Some(kind) => kind,
+            None => return Ok(()),
+        };
+        let ball_object = self.pool_table.world.body_part(handle.clone());
+        let pos = ball_object.position().translation.vector;
+        let radius = self.pool_table.profile.ball_radius;
+        let perspective = 1. + self.pool_table.height(*handle) / (radius * CAMERA_HEIGHT);
+        let size = self.camera.scale(radius * 2. * perspective);
+
+        let orientation = self
+            .orientations
+            .get(handle)
+            .cloned()
+            .unwrap_or_else(na::UnitQuaternion::identity);
+        let image = self.ball_sprites.get(kind, &orientation)?;
+        window.draw_ex(
+            &Rectangle::new_sized((size, size)).with_center(self.camera.to_screen(pos)),
+            Img(image),
+            Transform::rotate(self.camera.rotation()),
+            0,
+        );
+        Ok(())
+    }
+
+    // the band uses the cloth texture, darkened.
+    fn band_tint(&self) -> Color {
+        let (band, cloth) = (sprite::BAND_COLOR, sprite::CLOTH_COLOR);
+        Color::WHITE
+            .with_red(band[0] as f32 / cloth[0] as f32)
+            .with_green(band[1] as f32 / cloth[1] as f32)
+            .with_blue(band[2] as f32 / cloth[2] as f32)
+    }
+
+    fn hole_color(&self) -> Color {
+        let hole = sprite::HOLE_COLOR;
+        Color::WHITE
+            .with_red(hole[0] as f32 / 0xff as f32)
+            .with_green(hole[1] as f32 / 0xff as f32)
+            .with_blue(hole[2] as f32 / 0xff as f32)
+    }
+
+    fn draw_hole(&self, window: &mut Window, handle: &BodyHandle) {
+        let hole_object = self.pool_table.world.body_part(handle.clone());
+        let pos = hole_object.position().clone();
+        let pos = pos.translation.vector;
+        //info!("Ball pos: {:?}", pos);
+        let ball_ball = Ball::new(self.camera.scale(HOLE_SIZE));
+
+        window.draw(
+            &Circle::from_ball(self.camera.to_screen(pos), ball_ball),
+            Col(self.hole_color()),
+        );
+    }
+
+    fn draw_table(&self, window: &mut Window) -> Result<()> {
+        let background = Color::WHITE
+            .with_red(0xcc as f32 / 0xff as f32)
+            .with_green(0xcc as f32 / 0xff as f32)
+            .with_blue(0xcc as f32 / 0xff as f32);
+
+        window.clear(background)?;
+
+        // the textures follow the table when the camera rotates it
+        let rotation = Transform::rotate(self.camera.rotation());
+        window.draw_ex(
+            &self.camera.world_rect(
+                Vector2::new(MARGIN_LEFT, MARGIN_TOP),
+                Vector2::new(WIDTH + BORDER * 2. + BAND * 2., HEIGHT + BORDER * 2. + BAND * 2.),
+            ),
+            Img(&self.wood),
+            rotation,
+            0,
+        );
+        window.draw_ex(
+            &self.camera.world_rect(
+                Vector2::new(MARGIN_LEFT + BORDER, MARGIN_TOP + BORDER),
+                Vector2::new(WIDTH + BAND * 2., HEIGHT + BAND * 2.),
+            ),
+            Blended(&self.cloth, self.band_tint()),
+            rotation,
+            0,
+        );
+        window.draw_ex(
+            &self.camera.world_rect(
+                Vector2::new(MARGIN_LEFT + BORDER + BAND, MARGIN_TOP + BORDER + BAND),
+                Vector2::new(WIDTH, HEIGHT),
+            ),
+            Img(&self.cloth),
+            rotation,
+            0,
+        );
+
+        Ok(())
+    }
+}
+
+fn texture(render: fn(u32, u32) -> Vec<u8>, (width, height): (u32, u32)) -> Result<Image> {
+    Image::from_raw(&render(width, height), width, height, PixelFormat::RGBA)
+}
+
+/// Size of the browser window, the canvas follows it.
+#[cfg(target_arch = "wasm32")]
+fn screen_size(_window: &Window) -> Vector {
+    let browser = stdweb::web::window();
+    Vector::new(browser.inner_width(), browser.inner_height())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn screen_size(window: &Window) -> Vector {
+    window.screen_size()
+}
+
+/// Open the window of the game.
+pub fn run() {
+    lifecycle::run::<PoolGameUI>(
+        "PoolTable",
+        Vector::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+        Settings::default(),
+    );
+}
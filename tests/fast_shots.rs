@@ -0,0 +1,130 @@
+//! Shots at the maximum force must not send a ball through a cushion.
+
+use ncollide2d::math::Vector as Vector2;
+
+use wasm_pool::physics::PhysicsProfile;
+use wasm_pool::shot::Shot;
+use wasm_pool::state::{BallState, TableState};
+use wasm_pool::{
+    BallKind, GameMode, PoolTable, TableEvent, BALL_SIZE, BAND, HEIGHT, MAX_FORCE, WIDTH,
+};
+
+const ANGLE_STEP: usize = 5;
+// the fast part of a shot, three seconds at 60 steps per second
+const STEPS: usize = 180;
+
+fn max_power(angle: f32) -> Shot {
+    Shot {
+        angle,
+        force: MAX_FORCE,
+        spin: Vector2::new(0., 0.),
+        elevation: 0.,
+    }
+}
+
+fn angles() -> impl Iterator<Item = f32> {
+    (0..360).step_by(ANGLE_STEP).map(|angle| angle as f32)
+}
+
+// every ball on the table is inside the cushions
+fn assert_on_cloth(pool_table: &PoolTable, angle: f32, step: usize) {
+    // the contacts let the balls sink a little in the cushions
+    let tolerance = BALL_SIZE * 0.5;
+    for ball in TableState::of(pool_table).balls {
+        let inside = ball.x > BAND + BALL_SIZE - tolerance
+            && ball.x < BAND + WIDTH - BALL_SIZE + tolerance
+            && ball.y > BAND + BALL_SIZE - tolerance
+            && ball.y < BAND + HEIGHT - BALL_SIZE + tolerance;
+        assert!(
+            inside,
+            "shot at {} degrees, step {}: {:?} went through a cushion",
+            angle, step, ball
+        );
+    }
+    for pocketed in pool_table.pocketed_balls() {
+        assert!(
+            pocketed.hole.is_some(),
+            "shot at {} degrees, step {}: the {:?} ball left the table",
+            angle,
+            step,
+            pocketed.kind
+        );
+    }
+}
+
+#[test]
+fn break_shots_stay_on_the_table() {
+    for angle in angles() {
+        let mut pool_table = PoolTable::new();
+        pool_table.initialze_world(GameMode::EightBall);
+        pool_table.shoot(&max_power(angle));
+        for step in 0..STEPS {
+            pool_table.step();
+            assert_on_cloth(&pool_table, angle, step);
+            if !pool_table.has_force() {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn lone_cue_ball_stays_on_the_table() {
+    let state = TableState {
+        mode: GameMode::Practice,
+        balls: vec![BallState {
+            kind: BallKind::White,
+            x: BAND + WIDTH * 0.5,
+            y: BAND + HEIGHT * 0.5,
+        }],
+        profile: PhysicsProfile::standard(),
+    };
+    for angle in angles() {
+        let mut pool_table = state.build();
+        pool_table.shoot(&max_power(angle));
+        for step in 0..STEPS {
+            pool_table.step();
+            assert_on_cloth(&pool_table, angle, step);
+        }
+    }
+}
+
+// A full speed step carries the cue ball a little less than 900 units. The
+// band is BAND thick, backed by the rail: from a short gap a single step
+// would take the ball across the band to the back of the rail, which would
+// push it off the table.
+#[test]
+fn cue_ball_next_to_a_cushion_bounces_off_it() {
+    let start = BALL_SIZE * 1.25;
+    let shots = [
+        (0., BAND + WIDTH - start, BAND + HEIGHT * 0.5),
+        (90., BAND + WIDTH * 0.25, BAND + HEIGHT - start),
+        (180., BAND + start, BAND + HEIGHT * 0.5),
+        (270., BAND + WIDTH * 0.25, BAND + start),
+    ];
+    for &(angle, x, y) in shots.iter() {
+        let state = TableState {
+            mode: GameMode::Practice,
+            balls: vec![BallState {
+                kind: BallKind::White,
+                x,
+                y,
+            }],
+            profile: PhysicsProfile::standard(),
+        };
+        let mut pool_table = state.build();
+        pool_table.shoot(&max_power(angle));
+        pool_table.take_events();
+
+        pool_table.step();
+        let hit = pool_table.take_events().iter().any(|event| match event {
+            TableEvent::CushionHit { .. } => true,
+            _ => false,
+        });
+        assert!(hit, "shot at {} degrees missed the cushion", angle);
+        for step in 1..STEPS {
+            pool_table.step();
+            assert_on_cloth(&pool_table, angle, step);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Invariants of the physics on random layouts and random shots, played
+//! without a window until the balls are at rest.
+
+use std::iter;
+
+use ncollide2d::math::Vector as Vector2;
+use nphysics2d::object::BodyHandle;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use wasm_pool::physics::PhysicsProfile;
+use wasm_pool::shot::{clamp_spin, Shot, MAX_ELEVATION};
+use wasm_pool::simulation;
+use wasm_pool::state::{BallState, TableState};
+use wasm_pool::{BallKind, GameMode, PoolTable, BALL_SIZE, BAND, HEIGHT, MAX_FORCE, WIDTH};
+
+// every case plays a whole shot
+const CASES: u32 = 32;
+const MAX_OBJECT_BALLS: usize = 15;
+// the solver pushes apart the balls that sink in each other, and in the
+// cushions, with a little energy
+const ENERGY_TOLERANCE: f32 = 0.01;
+// and lets the balls at rest sink a little in each other
+const OVERLAP_TOLERANCE: f32 = BALL_SIZE * 0.05;
+
+#[derive(Clone, Debug)]
+enum Layout {
+    Rack(GameMode),
+    Random(TableState),
+}
+
+impl Layout {
+    // none when the random balls overlap
+    fn build(&self) -> Option<PoolTable> {
+        match self {
+            Layout::Rack(mode) => {
+                let mut pool_table = PoolTable::new();
+                pool_table.initialze_world(*mode);
+                Some(pool_table)
+            }
+            Layout::Random(state) => state.build_checked().ok(),
+        }
+    }
+}
+
+fn ball_state(kind: BallKind) -> impl Strategy<Value = BallState> {
+    (
+        BAND + BALL_SIZE..BAND + WIDTH - BALL_SIZE,
+        BAND + BALL_SIZE..BAND + HEIGHT - BALL_SIZE,
+    )
+        .prop_map(move |(x, y)| BallState { kind, x, y })
+}
+
+// the black ball is unique, it is drawn on its own
+fn object_ball() -> impl Strategy<Value = BallState> {
+    prop_oneof![
+        Just(BallKind::Red),
+        Just(BallKind::Yellow),
+        (1u8..=9).prop_map(BallKind::Numbered),
+    ]
+    .prop_flat_map(ball_state)
+}
+
+fn layout() -> impl Strategy<Value = Layout> {
+    let random = (
+        ball_state(BallKind::White),
+        proptest::option::of(ball_state(BallKind::Black)),
+        vec(object_ball(), 0..MAX_OBJECT_BALLS),
+    )
+        .prop_map(|(white, black, balls)| {
+            Layout::Random(TableState {
+                mode: GameMode::Practice,
+                balls: iter::once(white).chain(black).chain(balls).collect(),
+                profile: PhysicsProfile::standard(),
+            })
+        });
+    prop_oneof![
+        1 => Just(Layout::Rack(GameMode::EightBall)),
+        1 => Just(Layout::Rack(GameMode::NineBall)),
+        1 => Just(Layout::Rack(GameMode::Practice)),
+        3 => random,
+    ]
+}
+
+// miscues, jumps and massés included
+fn any_shot() -> impl Strategy<Value = Shot> {
+    (
+        0f32..360.,
+        0f32..=MAX_FORCE,
+        -1f32..=1.,
+        -1f32..=1.,
+        0f32..=MAX_ELEVATION,
+    )
+        .prop_map(|(angle, force, side, follow, elevation)| Shot {
+            angle,
+            force,
+            spin: clamp_spin(Vector2::new(side, follow)),
+            elevation,
+        })
+}
+
+// the table at rest after the shot, with the balls before it
+fn play(layout: &Layout, shot: &Shot) -> Result<(PoolTable, Vec<BodyHandle>), TestCaseError> {
+    let mut pool_table = match layout.build() {
+        Some(pool_table) => pool_table,
+        None => return Err(TestCaseError::reject("the balls overlap")),
+    };
+    let balls = pool_table.balls();
+    simulation::play(&mut pool_table, shot, |_, _, _| {});
+    prop_assert!(!pool_table.has_force(), "the balls never came to rest");
+    Ok((pool_table, balls))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    // the energy of the spin and the heights of the jumps included
+    #[test]
+    fn energy_never_rises_after_the_cue_impact(layout in layout(), shot in any_shot()) {
+        let mut pool_table = match layout.build() {
+            Some(pool_table) => pool_table,
+            None => return Err(TestCaseError::reject("the balls overlap")),
+        };
+        let mut energy = None;
+        simulation::play(&mut pool_table, &shot, |step, pool_table, _| {
+            let next = pool_table.energy();
+            if let Some(previous) = energy {
+                assert!(
+                    next <= previous * (1. + ENERGY_TOLERANCE),
+                    "step {}: the energy rose from {} to {}",
+                    step,
+                    previous,
+                    next
+                );
+            }
+            energy = Some(next);
+        });
+    }
+
+    #[test]
+    fn balls_do_not_overlap_at_rest(layout in layout(), shot in any_shot()) {
+        let (pool_table, _) = play(&layout, &shot)?;
+        let balls = TableState::of(&pool_table).balls;
+        let radius = pool_table.profile().ball_radius;
+        for (index, ball) in balls.iter().enumerate() {
+            for other in balls[index + 1..].iter() {
+                let distance = ((ball.x - other.x).powi(2) + (ball.y - other.y).powi(2)).sqrt();
+                prop_assert!(
+                    distance > radius * 2. - OVERLAP_TOLERANCE,
+                    "{:?} and {:?} overlap",
+                    ball,
+                    other
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn balls_are_on_the_table_or_dropped(layout in layout(), shot in any_shot()) {
+        let (pool_table, balls) = play(&layout, &shot)?;
+        for ball in balls {
+            let dropped = pool_table.dropped_balls().contains(&ball);
+            match pool_table.ball_position(ball) {
+                Some((x, y)) => {
+                    prop_assert!(!dropped, "{:?} is on the table and dropped", ball);
+                    prop_assert!(
+                        x > 0. && x < WIDTH + 2. * BAND && y > 0. && y < HEIGHT + 2. * BAND,
+                        "{:?} is off the table at ({}, {})",
+                        ball,
+                        x,
+                        y
+                    );
+                }
+                None => prop_assert!(dropped, "{:?} disappeared", ball),
+            }
+        }
+    }
+
+    #[test]
+    fn ball_count_is_conserved(layout in layout(), shot in any_shot()) {
+        let (pool_table, balls) = play(&layout, &shot)?;
+        let dropped = balls
+            .iter()
+            .filter(|ball| pool_table.dropped_balls().contains(ball))
+            .count();
+        prop_assert_eq!(pool_table.balls().len() + dropped, balls.len());
+    }
+}